@@ -0,0 +1,369 @@
+//! Derive macros for `orchestra-rs`.
+//!
+//! Enable them through the `derive` feature of `orchestra-rs` rather than depending on
+//! this crate directly; the generated code refers to items in `orchestra_rs::tools`.
+//!
+//! - `#[derive(ToolSchema)]` describes a struct with named fields, or an enum made of
+//!   unit variants, as a JSON schema. Use it for types nested inside tool arguments.
+//! - `#[derive(ToolArgs)]` does the same for a struct and additionally marks it as a
+//!   tool's complete argument list. The struct must also implement `Deserialize`.
+//!
+//! Doc comments become `description`s, `Option` fields are optional and the
+//! `rename`, `rename_all`, `default`, `skip` and `skip_deserializing` serde attributes
+//! are honored, including the `rename(deserialize = "...")` forms, so the schema matches
+//! what deserialization accepts. Other serde attributes are ignored.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Lit, LitStr, Meta, Token,
+    ext::IdentExt, meta::ParseNestedMeta, parse_macro_input, parse_quote,
+};
+
+/// Derive `orchestra_rs::tools::ToolSchema` for a struct or a unit-variant enum
+#[proc_macro_derive(ToolSchema, attributes(serde))]
+pub fn derive_tool_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_tool_schema(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `orchestra_rs::tools::ToolArgs` (and `ToolSchema`) for a struct
+#[proc_macro_derive(ToolArgs, attributes(serde))]
+pub fn derive_tool_args(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_tool_args(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_tool_args(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !matches!(input.data, Data::Struct(_)) {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "ToolArgs can only be derived for structs with named fields",
+        ));
+    }
+
+    let schema_impl = expand_tool_schema(input)?;
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(Self: ::orchestra_rs::tools::__private::DeserializeOwned));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #schema_impl
+
+        impl #impl_generics ::orchestra_rs::tools::ToolArgs for #name #ty_generics #where_clause {}
+    })
+}
+
+fn expand_tool_schema(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let container = SerdeAttrs::parse(&input.attrs)?;
+    let description = option_tokens(doc_comment(&input.attrs));
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let Fields::Named(fields) = &data.fields else {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "tool schemas can only be derived for structs with named fields",
+                ));
+            };
+
+            let mut entries = Vec::new();
+            for field in &fields.named {
+                let attrs = SerdeAttrs::parse(&field.attrs)?;
+                if attrs.skip {
+                    continue;
+                }
+                if attrs.flatten {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "#[serde(flatten)] is not supported in tool schemas",
+                    ));
+                }
+
+                let ident = field.ident.as_ref().expect("named field").unraw().to_string();
+                let name = attrs.rename.unwrap_or_else(|| {
+                    container
+                        .rename_all
+                        .map_or(ident.clone(), |rule| rule.apply_to_field(&ident))
+                });
+                let field_description = option_tokens(doc_comment(&field.attrs));
+                let ty = &field.ty;
+                // Fields with a serde default may be left out by the model
+                let required = if container.default || attrs.default {
+                    quote!(false)
+                } else {
+                    quote!(<#ty as ::orchestra_rs::tools::ToolSchema>::REQUIRED)
+                };
+
+                entries.push(quote! {
+                    ::orchestra_rs::tools::__private::Field {
+                        name: #name,
+                        description: #field_description,
+                        schema: <#ty as ::orchestra_rs::tools::ToolSchema>::json_schema(),
+                        required: #required,
+                    }
+                });
+            }
+
+            quote! {
+                ::orchestra_rs::tools::__private::object_schema(
+                    #description,
+                    ::std::vec![#(#entries),*],
+                )
+            }
+        }
+        Data::Enum(data) => {
+            let mut variants = Vec::new();
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "tool schemas can only be derived for enums whose variants have no fields",
+                    ));
+                }
+
+                let attrs = SerdeAttrs::parse(&variant.attrs)?;
+                if attrs.skip {
+                    continue;
+                }
+
+                let ident = variant.ident.unraw().to_string();
+                variants.push(attrs.rename.unwrap_or_else(|| {
+                    container
+                        .rename_all
+                        .map_or(ident.clone(), |rule| rule.apply_to_variant(&ident))
+                }));
+            }
+
+            quote! {
+                ::orchestra_rs::tools::__private::enum_schema(#description, &[#(#variants),*])
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "tool schemas cannot be derived for unions",
+            ));
+        }
+    };
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param
+            .bounds
+            .push(parse_quote!(::orchestra_rs::tools::ToolSchema));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::orchestra_rs::tools::ToolSchema for #name #ty_generics #where_clause {
+            fn json_schema() -> ::orchestra_rs::tools::__private::Value {
+                #body
+            }
+        }
+    })
+}
+
+/// Join the `///` doc comment lines of an item into a single description
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+fn option_tokens(value: Option<String>) -> TokenStream2 {
+    match value {
+        Some(value) => quote!(::std::option::Option::Some(#value)),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+/// The subset of serde attributes that changes what a schema should look like
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<RenameRule>,
+    default: bool,
+    skip: bool,
+    flatten: bool,
+}
+
+impl SerdeAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    // Either `rename = "name"` or `rename(deserialize = "name")`
+                    if meta.input.peek(Token![=]) {
+                        result.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else {
+                        meta.parse_nested_meta(|nested| {
+                            let value = nested.value()?.parse::<LitStr>()?.value();
+                            if nested.path.is_ident("deserialize") {
+                                result.rename = Some(value);
+                            }
+                            Ok(())
+                        })?;
+                    }
+                } else if meta.path.is_ident("rename_all") {
+                    // Either `rename_all = "rule"` or `rename_all(deserialize = "rule")`
+                    if meta.input.peek(Token![=]) {
+                        result.rename_all = Some(RenameRule::parse(&meta)?);
+                    } else {
+                        meta.parse_nested_meta(|nested| {
+                            let rule = RenameRule::parse(&nested)?;
+                            if nested.path.is_ident("deserialize") {
+                                result.rename_all = Some(rule);
+                            }
+                            Ok(())
+                        })?;
+                    }
+                } else if meta.path.is_ident("default") {
+                    if meta.input.peek(Token![=]) {
+                        meta.value()?.parse::<LitStr>()?;
+                    }
+                    result.default = true;
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_deserializing") {
+                    result.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    result.flatten = true;
+                } else {
+                    // Other serde attributes do not affect the schema
+                    skip_meta(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Consume the value of an attribute, in any of the `name`, `name = value` and
+/// `name(...)` forms, without interpreting it
+fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|nested| skip_meta(&nested))?;
+    }
+    Ok(())
+}
+
+/// Mirrors serde's `rename_all` rules
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    /// Parse the `= "rule"` part of a `rename_all` attribute
+    fn parse(meta: &ParseNestedMeta) -> syn::Result<Self> {
+        let rule = meta.value()?.parse::<LitStr>()?;
+        Self::from_str(&rule.value()).ok_or_else(|| meta.error("unknown rename_all rule"))
+    }
+
+    fn from_str(rule: &str) -> Option<Self> {
+        Some(match rule {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return None,
+        })
+    }
+
+    /// Rename a `snake_case` field name
+    fn apply_to_field(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_string(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => field
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                        .unwrap_or_default()
+                })
+                .collect(),
+            Self::Camel => {
+                let pascal = Self::Pascal.apply_to_field(field);
+                let mut chars = pascal.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+
+    /// Rename a `PascalCase` enum variant name
+    fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            Self::Pascal => variant.to_string(),
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Upper => variant.to_ascii_uppercase(),
+            Self::Camel => {
+                let mut chars = variant.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+            Self::Snake => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            Self::ScreamingSnake => Self::Snake.apply_to_variant(variant).to_ascii_uppercase(),
+            Self::Kebab => Self::Snake.apply_to_variant(variant).replace('_', "-"),
+            Self::ScreamingKebab => Self::ScreamingSnake.apply_to_variant(variant).replace('_', "-"),
+        }
+    }
+}
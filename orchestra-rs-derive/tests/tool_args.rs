@@ -0,0 +1,141 @@
+use orchestra_rs::{
+    error::OrchestraError,
+    messages::ToolFunction,
+    tools::{ToolArgs, ToolDefinition, ToolSchema},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+/// Temperature unit
+#[derive(Debug, Deserialize, PartialEq, ToolSchema)]
+#[serde(rename_all = "snake_case")]
+enum Unit {
+    Celsius,
+    Fahrenheit,
+}
+
+/// Get the weather forecast for a city
+#[derive(Debug, Deserialize, PartialEq, ToolArgs)]
+#[serde(rename_all = "camelCase")]
+struct WeatherArgs {
+    /// Name of the city, e.g. "Paris"
+    city: String,
+    /// Number of days to forecast
+    forecast_days: Option<u32>,
+    unit: Unit,
+    #[serde(default)]
+    include_wind: bool,
+    #[serde(rename = "lang")]
+    language: Option<String>,
+    #[serde(skip)]
+    cached: bool,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+struct NoArgs {}
+
+#[derive(Debug, Deserialize, PartialEq, ToolArgs)]
+#[serde(rename_all(serialize = "snake_case", deserialize = "camelCase"), deny_unknown_fields)]
+struct SearchArgs {
+    search_query: String,
+    #[serde(alias = "max", skip_serializing_if = "Option::is_none")]
+    max_results: Option<u32>,
+}
+
+#[test]
+fn test_derived_schema() {
+    assert_eq!(
+        WeatherArgs::parameters_schema(),
+        json!({
+            "type": "object",
+            "description": "Get the weather forecast for a city",
+            "properties": {
+                "city": {"type": "string", "description": "Name of the city, e.g. \"Paris\""},
+                "forecastDays": {"type": "integer", "description": "Number of days to forecast"},
+                "unit": {
+                    "type": "string",
+                    "enum": ["celsius", "fahrenheit"],
+                    "description": "Temperature unit"
+                },
+                "includeWind": {"type": "boolean"},
+                "lang": {"type": "string"}
+            },
+            "required": ["city", "unit"]
+        })
+    );
+
+    assert_eq!(
+        Unit::json_schema(),
+        json!({"type": "string", "enum": ["celsius", "fahrenheit"], "description": "Temperature unit"})
+    );
+}
+
+#[test]
+fn test_nested_serde_attributes() {
+    // Only the deserialize half of `rename_all` shapes the arguments
+    assert_eq!(
+        SearchArgs::parameters_schema(),
+        json!({
+            "type": "object",
+            "properties": {
+                "searchQuery": {"type": "string"},
+                "maxResults": {"type": "integer"}
+            },
+            "required": ["searchQuery"]
+        })
+    );
+
+    assert_eq!(
+        SearchArgs::from_arguments(&json!({"searchQuery": "rust", "max": 5})).unwrap(),
+        SearchArgs {
+            search_query: "rust".to_string(),
+            max_results: Some(5),
+        }
+    );
+}
+
+#[test]
+fn test_derived_definition() {
+    let tool = ToolDefinition::from_args::<WeatherArgs>("get_weather", "Get the weather");
+
+    assert_eq!(tool.name, "get_weather");
+    assert_eq!(tool.parameters, WeatherArgs::parameters_schema());
+}
+
+#[test]
+fn test_parse_arguments() {
+    let function = ToolFunction {
+        name: "get_weather".to_string(),
+        arguments: json!({"city": "Paris", "unit": "celsius", "lang": "fr"}),
+    };
+
+    assert_eq!(
+        function.parse_arguments::<WeatherArgs>().unwrap(),
+        WeatherArgs {
+            city: "Paris".to_string(),
+            forecast_days: None,
+            unit: Unit::Celsius,
+            include_wind: false,
+            language: Some("fr".to_string()),
+            cached: false,
+        }
+    );
+
+    assert!(NoArgs::from_arguments(&serde_json::Value::Null).is_ok());
+}
+
+#[test]
+fn test_parse_arguments_mismatch() {
+    let function = ToolFunction {
+        name: "get_weather".to_string(),
+        arguments: json!({"city": "Paris", "unit": "kelvin"}),
+    };
+
+    match function.parse_arguments::<WeatherArgs>() {
+        Err(OrchestraError::InvalidToolArguments { message, .. }) => {
+            assert!(message.starts_with("get_weather: "), "{}", message);
+            assert!(message.contains("kelvin"), "{}", message);
+        }
+        other => panic!("Expected invalid tool arguments error, got {:?}", other),
+    }
+}
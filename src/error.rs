@@ -1,16 +1,25 @@
+use std::time::Duration;
+
 use thiserror::Error;
 
-/// Main error type for the Orchestra library
+/// Main error type for the Orchestra library.
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum OrchestraError {
     /// HTTP request errors
-    #[error("HTTP request failed: {0}")]
-    Http(#[from] reqwest::Error),
+    #[error("HTTP request failed")]
+    Http(#[source] reqwest::Error),
 
     /// JSON serialization/deserialization errors
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// File system errors, e.g. while reading an attachment
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
     /// Invalid header value errors
     #[error("Invalid header value: {0}")]
     InvalidHeader(#[from] reqwest::header::InvalidHeaderValue),
@@ -21,7 +30,14 @@ pub enum OrchestraError {
 
     /// Provider-specific errors
     #[error("Provider error: {provider} - {message}")]
-    Provider { provider: String, message: String },
+    Provider {
+        provider: String,
+        message: String,
+        /// HTTP status of the failed response, if the error came from one
+        status: Option<u16>,
+        /// Raw body of the failed response
+        body: Option<String>,
+    },
 
     /// Configuration errors
     #[error("Configuration error: {message}")]
@@ -29,15 +45,43 @@ pub enum OrchestraError {
 
     /// Model not found or invalid
     #[error("Model error: {message}")]
-    Model { message: String },
+    Model {
+        message: String,
+        /// Provider that rejected the model
+        provider: Option<String>,
+        status: Option<u16>,
+        body: Option<String>,
+    },
 
     /// Rate limiting errors
     #[error("Rate limit exceeded: {message}")]
-    RateLimit { message: String },
+    RateLimit {
+        message: String,
+        provider: Option<String>,
+        /// How long the provider asked to wait, from the `Retry-After` header
+        retry_after: Option<Duration>,
+        status: Option<u16>,
+        body: Option<String>,
+    },
 
-    /// Authentication errors
+    /// Authentication errors, e.g. a missing or revoked API key (`401`) or missing
+    /// permissions (`403`)
     #[error("Authentication failed: {message}")]
-    Authentication { message: String },
+    Authentication {
+        message: String,
+        provider: Option<String>,
+        status: Option<u16>,
+        body: Option<String>,
+    },
+
+    /// The provider rejected the request as malformed (`400`)
+    #[error("Invalid request: {message}")]
+    InvalidRequest {
+        message: String,
+        provider: Option<String>,
+        status: Option<u16>,
+        body: Option<String>,
+    },
 
     /// Invalid response format
     #[error("Invalid response format: {message}")]
@@ -45,7 +89,57 @@ pub enum OrchestraError {
 
     /// Network timeout
     #[error("Request timeout: {message}")]
-    Timeout { message: String },
+    Timeout {
+        message: String,
+        /// The transport error that timed out
+        source: Option<reqwest::Error>,
+    },
+
+    /// The prompt or reply was blocked by the provider's safety filters
+    #[error("Content blocked: {reason}")]
+    ContentBlocked {
+        /// The provider's block reason, e.g. `SAFETY`
+        reason: String,
+        /// Harm categories that caused the block, e.g. `HARM_CATEGORY_HARASSMENT`
+        categories: Vec<String>,
+    },
+
+    /// A request was retried after transient errors and its last attempt still failed
+    #[error("Request failed after {attempts} attempts: {source}")]
+    RetryLimit {
+        attempts: u32,
+        /// The error of the last attempt
+        source: Box<OrchestraError>,
+    },
+
+    /// A tool-calling loop ran out of iterations before the model gave a final answer
+    #[error("Tool loop exceeded {max_iterations} iterations without a final answer")]
+    ToolLoopLimit { max_iterations: usize },
+
+    /// Tool call arguments chosen by the model do not match the expected type
+    #[error("Invalid tool arguments: {message}")]
+    InvalidToolArguments {
+        message: String,
+        /// The deserialization error, if there was one
+        source: Option<serde_json::Error>,
+    },
+
+    /// The model's reply could not be parsed into the requested type
+    #[error("Reply does not match {expected}: {message}")]
+    InvalidOutput {
+        /// Name of the type the reply was parsed into
+        expected: String,
+        /// Why parsing failed
+        message: String,
+        /// The reply as returned by the model
+        output: String,
+        /// The deserialization error, for replies parsed as JSON
+        source: Option<serde_json::Error>,
+    },
+
+    /// Every attempt to get a reply that parses failed
+    #[error("Reply could not be parsed after {} attempts", .attempts.len())]
+    OutputRetryLimit { attempts: Vec<OutputAttempt> },
 
     /// Generic errors for cases not covered above
     #[error("Orchestra error: {message}")]
@@ -65,9 +159,83 @@ impl OrchestraError {
         Self::Provider {
             provider: provider.into(),
             message: message.into(),
+            status: None,
+            body: None,
         }
     }
 
+    /// Create the error for an unsuccessful HTTP response, choosing the variant from
+    /// its status: `400` is an invalid request, `401` and `403` are authentication
+    /// errors and `429` is a rate limit. Other statuses become provider errors.
+    ///
+    /// A `404` only means a missing model for requests naming one; see
+    /// [`into_model_error`](Self::into_model_error).
+    pub fn from_status<P, M>(provider: P, status: u16, message: M, body: Option<String>) -> Self
+    where
+        P: Into<String>,
+        M: Into<String>,
+    {
+        let provider = provider.into();
+        let message = message.into();
+
+        match status {
+            400 => Self::InvalidRequest {
+                message,
+                provider: Some(provider),
+                status: Some(status),
+                body,
+            },
+            401 | 403 => Self::Authentication {
+                message,
+                provider: Some(provider),
+                status: Some(status),
+                body,
+            },
+            429 => Self::RateLimit {
+                message,
+                provider: Some(provider),
+                retry_after: None,
+                status: Some(status),
+                body,
+            },
+            _ => Self::Provider {
+                provider,
+                message,
+                status: Some(status),
+                body,
+            },
+        }
+    }
+
+    /// Treat a `404` response as a missing model, for errors of requests that name
+    /// the model to use, like chat requests. Other errors are returned unchanged.
+    pub fn into_model_error(self) -> Self {
+        match self {
+            Self::Provider {
+                provider,
+                message,
+                status: Some(404),
+                body,
+            } => Self::Model {
+                message,
+                provider: Some(provider),
+                status: Some(404),
+                body,
+            },
+            other => other,
+        }
+    }
+
+    /// Whether a response status is worth retrying; `529` is Anthropic's "overloaded"
+    pub fn is_retryable_status(status: u16) -> bool {
+        matches!(status, 429 | 500 | 502 | 503 | 504 | 529)
+    }
+
+    /// Whether a transport error is worth retrying: connection failures and timeouts
+    pub fn is_retryable_transport(error: &reqwest::Error) -> bool {
+        error.is_connect() || error.is_timeout()
+    }
+
     /// Create a new configuration error
     pub fn config<S: Into<String>>(message: S) -> Self {
         Self::Config {
@@ -79,6 +247,9 @@ impl OrchestraError {
     pub fn model<S: Into<String>>(message: S) -> Self {
         Self::Model {
             message: message.into(),
+            provider: None,
+            status: None,
+            body: None,
         }
     }
 
@@ -86,6 +257,10 @@ impl OrchestraError {
     pub fn rate_limit<S: Into<String>>(message: S) -> Self {
         Self::RateLimit {
             message: message.into(),
+            provider: None,
+            retry_after: None,
+            status: None,
+            body: None,
         }
     }
 
@@ -93,6 +268,19 @@ impl OrchestraError {
     pub fn authentication<S: Into<String>>(message: S) -> Self {
         Self::Authentication {
             message: message.into(),
+            provider: None,
+            status: None,
+            body: None,
+        }
+    }
+
+    /// Create a new invalid request error
+    pub fn invalid_request<S: Into<String>>(message: S) -> Self {
+        Self::InvalidRequest {
+            message: message.into(),
+            provider: None,
+            status: None,
+            body: None,
         }
     }
 
@@ -107,6 +295,38 @@ impl OrchestraError {
     pub fn timeout<S: Into<String>>(message: S) -> Self {
         Self::Timeout {
             message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Create a new content blocked error
+    pub fn content_blocked<S: Into<String>>(reason: S, categories: Vec<String>) -> Self {
+        Self::ContentBlocked {
+            reason: reason.into(),
+            categories,
+        }
+    }
+
+    /// Create a new invalid tool arguments error
+    pub fn invalid_tool_arguments<S: Into<String>>(message: S) -> Self {
+        Self::InvalidToolArguments {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Create a new invalid output error
+    pub fn invalid_output<E, M, O>(expected: E, message: M, output: O) -> Self
+    where
+        E: Into<String>,
+        M: Into<String>,
+        O: Into<String>,
+    {
+        Self::InvalidOutput {
+            expected: expected.into(),
+            message: message.into(),
+            output: output.into(),
+            source: None,
         }
     }
 
@@ -116,7 +336,158 @@ impl OrchestraError {
             message: message.into(),
         }
     }
+
+    /// Whether repeating the request may succeed: connection failures, timeouts, rate
+    /// limits and `500`, `502`, `503`, `504` and `529` responses.
+    ///
+    /// A [`RetryLimit`](Self::RetryLimit) error is classified by its last attempt, so
+    /// that e.g. a fallback to another provider can tell transient failures apart.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Http(error) => Self::is_retryable_transport(error),
+            Self::Timeout { .. } | Self::RateLimit { .. } => true,
+            Self::Provider {
+                status: Some(status),
+                ..
+            } => Self::is_retryable_status(*status),
+            Self::RetryLimit { source, .. } => source.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// The HTTP status of the failed response, if the error came from one
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            Self::Http(error) => error.status().map(|status| status.as_u16()),
+            Self::Provider { status, .. }
+            | Self::Model { status, .. }
+            | Self::RateLimit { status, .. }
+            | Self::Authentication { status, .. }
+            | Self::InvalidRequest { status, .. } => *status,
+            Self::RetryLimit { source, .. } => source.status_code(),
+            _ => None,
+        }
+    }
+
+    /// The name of the provider that reported the error, if known.
+    ///
+    /// Named so as not to clash with the [`provider`](Self::provider) constructor.
+    pub fn provider_name(&self) -> Option<&str> {
+        match self {
+            Self::Provider { provider, .. } => Some(provider),
+            Self::Model { provider, .. }
+            | Self::RateLimit { provider, .. }
+            | Self::Authentication { provider, .. }
+            | Self::InvalidRequest { provider, .. } => provider.as_deref(),
+            Self::RetryLimit { source, .. } => source.provider_name(),
+            _ => None,
+        }
+    }
+
+    /// How long the provider asked to wait before retrying, if it said
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimit { retry_after, .. } => *retry_after,
+            Self::RetryLimit { source, .. } => source.retry_after(),
+            _ => None,
+        }
+    }
+}
+
+/// A reply that failed to parse, as reported by [`OrchestraError::OutputRetryLimit`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputAttempt {
+    /// The reply as returned by the model
+    pub output: String,
+    /// Why parsing failed
+    pub message: String,
+}
+
+impl From<reqwest::Error> for OrchestraError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            // The transport error is kept as the source, so its text is not repeated here
+            Self::Timeout {
+                message: "no response from the server in time".to_string(),
+                source: Some(error),
+            }
+        } else {
+            Self::Http(error)
+        }
+    }
 }
 
 /// Result type alias for Orchestra operations
 pub type Result<T> = std::result::Result<T, OrchestraError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_from_status() {
+        let error = OrchestraError::from_status("gemini", 429, "Quota exceeded", Some("{}".to_string()));
+        assert!(matches!(error, OrchestraError::RateLimit { .. }));
+        assert!(error.is_retryable());
+        assert_eq!(error.status_code(), Some(429));
+        assert_eq!(error.provider_name(), Some("gemini"));
+
+        // A 404 is only a missing model where the request named one
+        let error = OrchestraError::from_status("openai", 404, "The model `gpt-9` does not exist", None);
+        assert!(matches!(error, OrchestraError::Provider { .. }));
+        let error = error.into_model_error();
+        assert!(matches!(error, OrchestraError::Model { .. }));
+        assert_eq!(error.status_code(), Some(404));
+        assert_eq!(error.provider_name(), Some("openai"));
+        assert!(!error.is_retryable());
+
+        let error = OrchestraError::from_status("openai", 500, "Server error", None).into_model_error();
+        assert!(matches!(error, OrchestraError::Provider { .. }));
+
+        let error = OrchestraError::from_status("openai", 401, "Invalid API key", None);
+        assert!(matches!(error, OrchestraError::Authentication { .. }));
+        assert!(!error.is_retryable());
+
+        // Anthropic reports overload with a non-standard status
+        let error = OrchestraError::from_status("anthropic", 529, "Overloaded", None);
+        assert!(matches!(error, OrchestraError::Provider { ref message, .. } if message == "Overloaded"));
+        assert!(error.is_retryable());
+
+        let error = OrchestraError::from_status("anthropic", 503, "Unavailable", None);
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_retry_limit_reports_last_attempt() {
+        let error = OrchestraError::RetryLimit {
+            attempts: 3,
+            source: Box::new(OrchestraError::RateLimit {
+                message: "Slow down".to_string(),
+                provider: Some("gemini".to_string()),
+                retry_after: Some(Duration::from_secs(5)),
+                status: Some(429),
+                body: None,
+            }),
+        };
+
+        assert!(error.is_retryable());
+        assert_eq!(error.status_code(), Some(429));
+        assert_eq!(error.provider_name(), Some("gemini"));
+        assert_eq!(error.retry_after(), Some(Duration::from_secs(5)));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn test_errors_without_metadata() {
+        let error = OrchestraError::config("Missing model");
+        assert!(!error.is_retryable());
+        assert_eq!(error.status_code(), None);
+        assert_eq!(error.provider_name(), None);
+        assert_eq!(error.retry_after(), None);
+
+        let json_error = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let error = OrchestraError::from(json_error);
+        assert!(error.source().is_some());
+    }
+}
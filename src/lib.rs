@@ -40,14 +40,18 @@
 //! - [`llm`]: High-level interface for interacting with LLMs
 //! - [`messages`]: Message types for conversations
 //! - [`model`]: Model configuration and settings
+//! - [`parsers`]: Parsers turning replies into typed values
 //! - [`providers`]: LLM provider implementations
+//! - [`tools`]: Tool (function calling) definitions
 //! - [`error`]: Error types and handling
 
 pub mod error;
 pub mod llm;
 pub mod messages;
 pub mod model;
+pub mod parsers;
 pub mod providers;
+pub mod tools;
 
 // Re-export commonly used types
 pub use error::{OrchestraError, Result};
@@ -41,15 +41,24 @@
 //! }
 //! ```
 
+use serde::de::DeserializeOwned;
+
 use crate::{
-    error::Result,
+    error::{OrchestraError, OutputAttempt, Result},
     messages::Message,
     model::ModelConfig,
+    parsers::{JsonParser, OutputParser},
     providers::{
         ProviderExt,
+        anthropic::AnthropicProvider,
         gemini::GeminiProvider,
-        types::{ChatResponse, ProviderSource},
+        ollama::OllamaProvider,
+        Provider,
+        config::ProviderConfig,
+        openai::{OpenAICompatibleProvider, OpenAIProvider},
+        types::{ChatResponse, ChatStream, ProviderSource},
     },
+    tools::ToolRegistry,
 };
 
 /// High-level interface for interacting with Large Language Models.
@@ -110,7 +119,8 @@ impl LLM {
     /// The returned LLM uses a provider implementation chosen from `provider_source` and
     /// initializes `config` using `ModelConfig::new(&model_name)`.
     ///
-    /// Panics if `provider_source` is not supported. Currently only `ProviderSource::Gemini` is supported.
+    /// Providers are created with their default configuration. `ProviderSource::OpenAICompatible`
+    /// has no default endpoint, so use [`LLM::openai_compatible`] to supply its base URL.
     ///
     /// # Examples
     ///
@@ -126,10 +136,12 @@ impl LLM {
 
         let provider: Box<dyn ProviderExt> = match provider_source {
             ProviderSource::Gemini => Box::new(GeminiProvider::with_default_config()),
-            _ => panic!(
-                "Unsupported provider source: {:?}. Supported providers: Gemini",
-                provider_source
-            ),
+            ProviderSource::OpenAI => Box::new(OpenAIProvider::with_default_config()),
+            ProviderSource::OpenAICompatible => {
+                Box::new(OpenAICompatibleProvider::new(ProviderConfig::default()))
+            }
+            ProviderSource::Anthropic => Box::new(AnthropicProvider::with_default_config()),
+            ProviderSource::Ollama => Box::new(OllamaProvider::with_default_config()),
         };
 
         LLM {
@@ -144,6 +156,47 @@ impl LLM {
         Self::new(ProviderSource::Gemini, model_name.into())
     }
 
+    /// Create a new LLM instance with OpenAI provider
+    pub fn openai<S: Into<String>>(model_name: S) -> Self {
+        Self::new(ProviderSource::OpenAI, model_name.into())
+    }
+
+    /// Create a new LLM instance for any server speaking the OpenAI wire format.
+    ///
+    /// See [`OpenAICompatibleProvider`] for the `config` fields it reads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orchestra_rs::{llm::LLM, providers::config::ProviderConfig};
+    ///
+    /// let config = ProviderConfig::new().with_base_url("http://localhost:1234/v1");
+    /// let llm = LLM::openai_compatible(config, "qwen2.5-7b-instruct");
+    /// assert_eq!(llm.provider_name(), "openai_compatible");
+    /// ```
+    pub fn openai_compatible<S: Into<String>>(config: ProviderConfig, model_name: S) -> Self {
+        let model_name = model_name.into();
+
+        LLM {
+            provider_source: ProviderSource::OpenAICompatible,
+            provider: Box::new(OpenAICompatibleProvider::new(config)),
+            config: ModelConfig::new(&model_name),
+        }
+    }
+
+    /// Create a new LLM instance with Anthropic provider
+    pub fn anthropic<S: Into<String>>(model_name: S) -> Self {
+        Self::new(ProviderSource::Anthropic, model_name.into())
+    }
+
+    /// Create a new LLM instance with a local Ollama provider
+    ///
+    /// Ollama has no fixed model list: `get_predefined_models` returns an empty list
+    /// until `list_models` has fetched the locally installed models from the server.
+    pub fn ollama<S: Into<String>>(model_name: S) -> Self {
+        Self::new(ProviderSource::Ollama, model_name.into())
+    }
+
     /// Create a new LLM instance with conservative settings
     pub fn conservative(provider_source: ProviderSource, model_name: String) -> Self {
         let config = ModelConfig::conservative(&model_name);
@@ -254,6 +307,258 @@ impl LLM {
         self.provider.chat(config, message, history).await
     }
 
+    /// Send a single prompt and parse the model's JSON reply into `T`.
+    ///
+    /// See [`LLM::chat_typed`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use orchestra_rs::{llm::LLM, model::ModelConfig};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Capital {
+    ///     city: String,
+    ///     country: String,
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let schema = serde_json::json!({
+    ///         "type": "object",
+    ///         "properties": {"city": {"type": "string"}, "country": {"type": "string"}},
+    ///         "required": ["city", "country"]
+    ///     });
+    ///     let llm = LLM::gemini("gemini-2.5-flash")
+    ///         .with_custom_config(ModelConfig::new("gemini-2.5-flash").with_response_schema(schema));
+    ///
+    ///     let capital: Capital = llm.prompt_typed("What is the capital of France?").await?;
+    ///     println!("{} is the capital of {}", capital.city, capital.country);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn prompt_typed<T, S>(&self, prompt: S) -> Result<T>
+    where
+        T: DeserializeOwned,
+        S: Into<String>,
+    {
+        self.chat_typed(Message::human(prompt), Vec::new()).await
+    }
+
+    /// Send a chat message and parse the model's JSON reply into `T`.
+    ///
+    /// JSON output is requested from the provider unless the configuration already
+    /// sets a response MIME type or declares tools; set a `response_schema` as well to
+    /// constrain the shape of the reply. A reply wrapped in a Markdown code fence is accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OrchestraError::InvalidOutput`], carrying the raw reply, if it does
+    /// not deserialize into `T`, plus any error from the provider.
+    pub async fn chat_typed<T: DeserializeOwned>(
+        &self,
+        message: Message,
+        history: Vec<Message>,
+    ) -> Result<T> {
+        let parser = JsonParser::<T>::new();
+        let mut config = self.config.clone();
+        parser.configure(&mut config);
+
+        let response = self.provider.chat(config, message, history).await?;
+        parser.parse(&response.text)
+    }
+
+    /// Send a single prompt and parse the reply with `parser`.
+    ///
+    /// See [`LLM::chat_with_parser`].
+    pub async fn prompt_with_parser<P, S>(
+        &self,
+        prompt: S,
+        parser: &P,
+        max_retries: usize,
+    ) -> Result<P::Output>
+    where
+        P: OutputParser,
+        S: Into<String>,
+    {
+        self.chat_with_parser(Message::human(prompt), Vec::new(), parser, max_retries)
+            .await
+    }
+
+    /// Send a chat message and parse the reply with `parser`, re-prompting on failure.
+    ///
+    /// When a reply fails to parse, it is added to the conversation together with a
+    /// message explaining the error and the parser's format instructions, and the model
+    /// is asked again, up to `max_retries` times.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OrchestraError::OutputRetryLimit`] with every failed reply if none
+    /// could be parsed. Provider errors are returned immediately.
+    pub async fn chat_with_parser<P: OutputParser>(
+        &self,
+        message: Message,
+        history: Vec<Message>,
+        parser: &P,
+        max_retries: usize,
+    ) -> Result<P::Output> {
+        let mut config = self.config.clone();
+        parser.configure(&mut config);
+
+        let mut history = history;
+        let mut message = message;
+        let mut attempts = Vec::new();
+
+        for _ in 0..=max_retries {
+            let response = self
+                .provider
+                .chat(config.clone(), message.clone(), history.clone())
+                .await?;
+
+            let error = match parser.parse(&response.text) {
+                Ok(output) => return Ok(output),
+                Err(OrchestraError::InvalidOutput { message, .. }) => message,
+                Err(e) => e.to_string(),
+            };
+
+            let mut correction = format!("Your reply could not be parsed: {}.", error);
+            if let Some(instructions) = parser.format_instructions() {
+                correction.push(' ');
+                correction.push_str(&instructions);
+            }
+
+            history.push(message);
+            history.push(Message::assistant(response.text.clone()));
+            message = Message::human(correction);
+            attempts.push(OutputAttempt {
+                output: response.text,
+                message: error,
+            });
+        }
+
+        Err(OrchestraError::OutputRetryLimit { attempts })
+    }
+
+    /// Send a single prompt to the LLM and stream the model's response as it is generated.
+    ///
+    /// The returned [`ChatStream`] yields [`ChatStreamEvent`](crate::providers::types::ChatStreamEvent)s:
+    /// text deltas as they arrive, followed by the finish reason and token usage when the
+    /// provider reports them. Providers without native streaming emit the full response
+    /// as a single text delta.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::StreamExt;
+    /// use orchestra_rs::{llm::LLM, providers::types::ChatStreamEvent};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let llm = LLM::gemini("gemini-2.5-flash");
+    ///     let mut stream = llm.prompt_stream("Write a haiku about Rust").await?;
+    ///
+    ///     while let Some(event) = stream.next().await {
+    ///         if let ChatStreamEvent::TextDelta(text) = event? {
+    ///             print!("{}", text);
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn prompt_stream<S: Into<String>>(&self, prompt: S) -> Result<ChatStream> {
+        let config = self.config.clone();
+        self.provider.prompt_stream(config, prompt.into()).await
+    }
+
+    /// Send a chat message with conversation history and stream the model's response.
+    ///
+    /// This is the streaming counterpart of [`LLM::chat`]; see [`LLM::prompt_stream`]
+    /// for the events yielded by the returned stream.
+    pub async fn chat_stream(&self, message: Message, history: Vec<Message>) -> Result<ChatStream> {
+        let config = self.config.clone();
+        self.provider.chat_stream(config, message, history).await
+    }
+
+    /// Send a chat message and let the model call tools from `registry` until it answers.
+    ///
+    /// The registry's tool definitions are sent alongside any tools already in the model
+    /// configuration. Whenever the model responds with tool calls, they are executed
+    /// concurrently and their results are sent back as [`Message::Tool`] messages,
+    /// together with the growing conversation. The first response without tool calls is returned.
+    ///
+    /// Failing tools do not abort the loop; see [`ToolRegistry::execute`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OrchestraError::InvalidRequest`] without sending anything if the
+    /// provider does not support tool calling, and [`OrchestraError::ToolLoopLimit`]
+    /// if the model is still requesting tool calls after `max_iterations` round trips,
+    /// plus any error from the provider.
+    pub async fn run_with_tools(
+        &self,
+        message: Message,
+        history: Vec<Message>,
+        registry: &ToolRegistry,
+        max_iterations: usize,
+    ) -> Result<ChatResponse> {
+        if !self.provider.supports_tools() {
+            return Err(OrchestraError::invalid_request(format!(
+                "The {} provider does not support tool calling",
+                self.provider.name()
+            )));
+        }
+
+        let mut config = self.config.clone();
+        config.tools.extend(registry.definitions());
+
+        let mut history = history;
+        let mut message = message;
+
+        for _ in 0..max_iterations {
+            let response = self
+                .provider
+                .chat(config.clone(), message.clone(), history.clone())
+                .await?;
+
+            if !response.has_tool_calls() {
+                return Ok(response);
+            }
+
+            let text = Some(response.text.clone()).filter(|text| !text.is_empty());
+            history.push(message);
+            history.push(Message::assistant_tool_calls(text, response.tool_calls.clone()));
+
+            let results = futures::future::join_all(
+                response.tool_calls.iter().map(|call| registry.execute(call)),
+            )
+            .await;
+            history.extend(results.into_iter().map(Message::Tool));
+            // There is at least one result, since the response had tool calls
+            message = history.pop().expect("tool results");
+        }
+
+        Err(OrchestraError::ToolLoopLimit { max_iterations })
+    }
+
+    /// Returns the models available from the provider.
+    ///
+    /// For Ollama this queries the server's installed models; other providers
+    /// return their predefined list.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> orchestra_rs::error::Result<()> {
+    /// let llm = orchestra_rs::llm::LLM::ollama("llama3.2");
+    /// let models = llm.list_models().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_models(&self) -> Result<Vec<String>> {
+        self.provider.list_models().await
+    }
+
     /// Returns the provider's static name.
     ///
     /// # Examples
@@ -302,7 +607,23 @@ impl LLM {
 
 #[cfg(test)]
 mod tests {
-    use crate::providers::gemini;
+    use async_trait::async_trait;
+    use futures::StreamExt;
+
+    use crate::{
+        messages::{ToolCall, ToolFunction},
+        providers::{
+            anthropic,
+            config::OpenAIConfig,
+            gemini,
+            mock::{MockConfig, MockProvider},
+            openai,
+            test_server::{MockResponse, TestServer},
+            types::{ChatStreamEvent, FinishReason},
+        },
+        parsers::{ChoiceParser, JsonParser},
+        tools::Tool,
+    };
 
     use super::*;
 
@@ -328,5 +649,324 @@ mod tests {
             ProviderSource::Gemini,
             gemini::PREDEFINED_MODELS[0].to_string(),
         );
+
+        let openai_llm = LLM::openai(openai::PREDEFINED_MODELS[0]);
+        assert_eq!(openai_llm.provider_name(), "openai");
+        assert_eq!(openai_llm.get_model_name(), openai::PREDEFINED_MODELS[0]);
+
+        let anthropic_llm = LLM::anthropic(anthropic::PREDEFINED_MODELS[0]);
+        assert_eq!(anthropic_llm.provider_name(), "anthropic");
+
+        let ollama_llm = LLM::ollama("llama3.2");
+        assert_eq!(ollama_llm.provider_name(), "ollama");
+
+        let compatible_llm = LLM::openai_compatible(
+            ProviderConfig::new().with_base_url("http://localhost:8000/v1"),
+            "llama-3.1-8b",
+        );
+        assert_eq!(compatible_llm.provider.get_base_url(), "http://localhost:8000/v1");
+        assert_eq!(compatible_llm.get_model_name(), "llama-3.1-8b");
+    }
+
+    #[tokio::test]
+    async fn test_llm_chat_stream() {
+        let llm = LLM {
+            provider_source: ProviderSource::Gemini,
+            provider: Box::new(MockProvider::new(
+                MockConfig::new().with_responses(vec!["Streamed reply"]),
+            )),
+            config: ModelConfig::new("mock-model-1"),
+        };
+
+        let mut stream = llm
+            .chat_stream(Message::human("Hi"), vec![Message::assistant("Hello")])
+            .await
+            .unwrap();
+
+        let mut text = String::new();
+        let mut finish_reason = None;
+        while let Some(event) = stream.next().await {
+            match event.unwrap() {
+                ChatStreamEvent::TextDelta(delta) => text.push_str(&delta),
+                ChatStreamEvent::Finish(reason) => finish_reason = Some(reason),
+                _ => {}
+            }
+        }
+
+        assert_eq!(text, "Streamed reply");
+        assert_eq!(finish_reason, Some(FinishReason::Stop));
+    }
+
+    struct Weather;
+
+    #[async_trait]
+    impl Tool for Weather {
+        fn name(&self) -> &str {
+            "get_weather"
+        }
+
+        fn description(&self) -> &str {
+            "Get the current weather for a city"
+        }
+
+        async fn call(&self, arguments: serde_json::Value) -> Result<serde_json::Value> {
+            Ok(serde_json::json!({ "city": arguments["city"], "forecast": "sunny" }))
+        }
+    }
+
+    fn weather_call() -> ChatResponse {
+        ChatResponse {
+            tool_calls: vec![ToolCall {
+                id: "get_weather_0".to_string(),
+                call_id: None,
+                function: ToolFunction {
+                    name: "get_weather".to_string(),
+                    arguments: serde_json::json!({"city": "Paris"}),
+                },
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_run_with_tools() {
+        let provider = MockProvider::new(MockConfig::new().with_chat_responses(vec![
+            weather_call(),
+            ChatResponse {
+                text: "It is sunny in Paris.".to_string(),
+                ..Default::default()
+            },
+        ]));
+        let requests = provider.requests.clone();
+        let llm = LLM {
+            provider_source: ProviderSource::Gemini,
+            provider: Box::new(provider),
+            config: ModelConfig::new("mock-model-1"),
+        };
+        let registry = ToolRegistry::new().with_tool(Weather);
+
+        let response = llm
+            .run_with_tools(Message::human("Weather in Paris?"), vec![], &registry, 3)
+            .await
+            .unwrap();
+
+        assert_eq!(response.text, "It is sunny in Paris.");
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].model_config.tools[0].name, "get_weather");
+
+        let second = &requests[1];
+        assert_eq!(second.chat_history.len(), 2);
+        assert_eq!(second.chat_history[0].content_text(), "Weather in Paris?");
+        match &second.chat_history[1] {
+            Message::Assistant(msg) => assert_eq!(msg.content.tool_calls().len(), 1),
+            other => panic!("Expected assistant tool calls, got {:?}", other),
+        }
+
+        let Message::Tool(result) = &second.message else {
+            panic!("Expected a tool result, got {:?}", second.message);
+        };
+        assert_eq!(result.id, "get_weather_0");
+        assert_eq!(result.name, "get_weather");
+        assert_eq!(
+            result.content.to_json(),
+            serde_json::json!({"city": "Paris", "forecast": "sunny"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_llm_run_with_tools_iteration_limit() {
+        let llm = LLM {
+            provider_source: ProviderSource::Gemini,
+            provider: Box::new(MockProvider::new(
+                MockConfig::new().with_chat_responses(vec![weather_call()]),
+            )),
+            config: ModelConfig::new("mock-model-1"),
+        };
+        let registry = ToolRegistry::new().with_tool(Weather);
+
+        let result = llm
+            .run_with_tools(Message::human("Weather in Paris?"), vec![], &registry, 2)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(OrchestraError::ToolLoopLimit { max_iterations: 2 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_llm_run_with_tools_unsupported_provider() {
+        let provider = MockProvider::new(MockConfig::new().with_tool_support(false));
+        let requests = provider.requests.clone();
+        let llm = LLM {
+            provider_source: ProviderSource::Anthropic,
+            provider: Box::new(provider),
+            config: ModelConfig::new("mock-model-1"),
+        };
+        let registry = ToolRegistry::new().with_tool(Weather);
+
+        let result = llm
+            .run_with_tools(Message::human("Weather in Paris?"), vec![], &registry, 3)
+            .await;
+
+        assert!(matches!(result, Err(OrchestraError::InvalidRequest { .. })));
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Capital {
+        city: String,
+        country: String,
+    }
+
+    #[tokio::test]
+    async fn test_llm_prompt_typed() {
+        let provider = MockProvider::new(MockConfig::new().with_responses(vec![
+            r#"{"city": "Paris", "country": "France"}"#,
+            "```json\n{\"city\": \"Rome\", \"country\": \"Italy\"}\n```",
+        ]));
+        let requests = provider.requests.clone();
+        let llm = LLM {
+            provider_source: ProviderSource::Gemini,
+            provider: Box::new(provider),
+            config: ModelConfig::new("mock-model-1"),
+        };
+
+        let capital: Capital = llm.prompt_typed("Capital of France?").await.unwrap();
+        assert_eq!(
+            capital,
+            Capital {
+                city: "Paris".to_string(),
+                country: "France".to_string()
+            }
+        );
+
+        let capital: Capital = llm.prompt_typed("Capital of Italy?").await.unwrap();
+        assert_eq!(capital.city, "Rome");
+
+        // JSON output is requested even though the configuration did not ask for it
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].model_config.wants_json());
+    }
+
+    #[tokio::test]
+    async fn test_llm_prompt_typed_mismatch() {
+        let llm = LLM {
+            provider_source: ProviderSource::Gemini,
+            provider: Box::new(MockProvider::new(
+                MockConfig::new().with_responses(vec![r#"{"city": "Paris"}"#]),
+            )),
+            config: ModelConfig::new("mock-model-1"),
+        };
+
+        match llm.prompt_typed::<Capital, _>("Capital of France?").await {
+            Err(OrchestraError::InvalidOutput {
+                expected,
+                message,
+                output,
+                ..
+            }) => {
+                assert!(expected.ends_with("Capital"), "{}", expected);
+                assert!(message.contains("country"), "{}", message);
+                assert_eq!(output, r#"{"city": "Paris"}"#);
+            }
+            other => panic!("Expected invalid output error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_chat_with_parser_retries() {
+        let provider = MockProvider::new(MockConfig::new().with_responses(vec![
+            "I think it's positive",
+            "Positive",
+        ]));
+        let requests = provider.requests.clone();
+        let llm = LLM {
+            provider_source: ProviderSource::Gemini,
+            provider: Box::new(provider),
+            config: ModelConfig::new("mock-model-1"),
+        };
+        let parser = ChoiceParser::new(["positive", "negative"]);
+
+        let sentiment = llm
+            .prompt_with_parser("Sentiment of 'great'?", &parser, 2)
+            .await
+            .unwrap();
+        assert_eq!(sentiment, "positive");
+
+        // The failed reply and the parse error are sent back to the model
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let retry = &requests[1];
+        assert_eq!(retry.chat_history.len(), 2);
+        assert!(matches!(&retry.chat_history[1], Message::Assistant(_)));
+        match &retry.message {
+            Message::Human(human) => {
+                let text = human.content.to_text();
+                assert!(text.contains("expected one of: positive, negative"), "{}", text);
+                assert!(text.contains("Respond with exactly one of"), "{}", text);
+            }
+            other => panic!("Expected a human message, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_chat_with_parser_retry_limit() {
+        let llm = LLM {
+            provider_source: ProviderSource::Gemini,
+            provider: Box::new(MockProvider::new(
+                MockConfig::new().with_responses(vec!["maybe", "unsure"]),
+            )),
+            config: ModelConfig::new("mock-model-1"),
+        };
+        let parser = ChoiceParser::new(["yes", "no"]);
+
+        match llm.prompt_with_parser("Is Rust fast?", &parser, 1).await {
+            Err(OrchestraError::OutputRetryLimit { attempts }) => {
+                let outputs: Vec<&str> = attempts.iter().map(|a| a.output.as_str()).collect();
+                assert_eq!(outputs, vec!["maybe", "unsure"]);
+                assert_eq!(attempts[0].message, "expected one of: yes, no");
+            }
+            other => panic!("Expected output retry limit error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_json_parser_with_openai() {
+        let server = TestServer::start(vec![MockResponse::json(serde_json::json!({
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "{\"city\": \"Paris\"}"},
+                "finish_reason": "stop"
+            }]
+        }))])
+        .await;
+        let llm = LLM {
+            provider_source: ProviderSource::OpenAI,
+            provider: Box::new(openai::OpenAIProvider::new(
+                OpenAIConfig::new()
+                    .with_api_key("test-key")
+                    .with_base_url(server.url()),
+            )),
+            config: ModelConfig::new("gpt-4o-mini"),
+        };
+
+        #[derive(serde::Deserialize)]
+        struct Capital {
+            city: String,
+        }
+
+        let reply: Capital = llm
+            .prompt_with_parser("What is the capital of France?", &JsonParser::new(), 0)
+            .await
+            .unwrap();
+        assert_eq!(reply.city, "Paris");
+
+        // JSON mode needs the messages to mention JSON
+        let body = server.requests()[0].json();
+        assert_eq!(body["response_format"]["type"], "json_object");
+        assert!(body["messages"][0]["content"].as_str().unwrap().contains("JSON"));
     }
 }
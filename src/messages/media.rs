@@ -0,0 +1,117 @@
+//! MIME type detection for file attachments.
+
+use std::path::Path;
+
+/// Leading bytes identifying common file formats
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"ID3", "audio/mpeg"),
+    (b"fLaC", "audio/flac"),
+    (b"OggS", "audio/ogg"),
+];
+
+/// Detect the MIME type of a file from its contents, falling back to its extension
+pub(crate) fn detect_mime_type(path: &Path, bytes: &[u8]) -> Option<&'static str> {
+    sniff(bytes).or_else(|| from_extension(path))
+}
+
+fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    if let Some((_, mime_type)) = SIGNATURES
+        .iter()
+        .find(|(signature, _)| bytes.starts_with(signature))
+    {
+        return Some(mime_type);
+    }
+
+    // RIFF containers carry their format at offset 8
+    if bytes.len() >= 12 && bytes.starts_with(b"RIFF") {
+        return match &bytes[8..12] {
+            b"WEBP" => Some("image/webp"),
+            b"WAVE" => Some("audio/wav"),
+            _ => None,
+        };
+    }
+
+    // ISO base media files (MP4, MOV, HEIC, ...) have an `ftyp` box at offset 4,
+    // whose major brand tells them apart. Unknown brands are left to the extension.
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return match &bytes[8..12] {
+            b"avif" | b"avis" => Some("image/avif"),
+            b"heic" | b"heix" | b"heim" | b"heis" | b"mif1" | b"msf1" => Some("image/heic"),
+            b"qt  " => Some("video/quicktime"),
+            b"M4A " | b"M4B " => Some("audio/mp4"),
+            b"isom" | b"iso2" | b"mp41" | b"mp42" | b"avc1" | b"dash" | b"M4V " => {
+                Some("video/mp4")
+            }
+            _ => None,
+        };
+    }
+
+    None
+}
+
+fn from_extension(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "avif" => "image/avif",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "ogg" => "audio/ogg",
+        "aac" => "audio/aac",
+        "m4a" => "audio/mp4",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_mime_type() {
+        let path = Path::new("attachment");
+
+        assert_eq!(detect_mime_type(path, b"\xff\xd8\xff\xe0"), Some("image/jpeg"));
+        assert_eq!(detect_mime_type(path, b"%PDF-1.7"), Some("application/pdf"));
+        assert_eq!(
+            detect_mime_type(path, b"RIFF\x24\0\0\0WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(detect_mime_type(path, b"\0\0\0\x18ftypmp42"), Some("video/mp4"));
+        assert_eq!(detect_mime_type(path, b"\0\0\0\x1cftypavif"), Some("image/avif"));
+        assert_eq!(detect_mime_type(path, b"\0\0\0\x18ftypmif1"), Some("image/heic"));
+        assert_eq!(detect_mime_type(path, b"\0\0\0\x20ftypM4A "), Some("audio/mp4"));
+        assert_eq!(detect_mime_type(path, b"\0\0\0\x18ftypcrx "), None);
+        assert_eq!(detect_mime_type(path, b"hello"), None);
+
+        // Contents win over a misleading extension
+        assert_eq!(
+            detect_mime_type(Path::new("photo.jpg"), b"\x89PNG\r\n\x1a\n"),
+            Some("image/png")
+        );
+        assert_eq!(
+            detect_mime_type(Path::new("notes.MD"), b"# Title"),
+            Some("text/markdown")
+        );
+    }
+}
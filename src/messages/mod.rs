@@ -1,5 +1,12 @@
+pub(crate) mod media;
+
+use std::path::Path;
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
 use serde::{Deserialize, Serialize};
 
+use crate::error::{OrchestraError, Result};
+
 /// Represents different types of messages in a conversation
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Message {
@@ -9,6 +16,8 @@ pub enum Message {
     Assistant(AssistantMessage),
     /// System instruction or context message
     System(SystemMessage),
+    /// Result of a tool call, sent back to the model
+    Tool(ToolMessage),
 }
 
 /// Message from a human user
@@ -29,6 +38,24 @@ pub struct SystemMessage {
     pub content: String,
 }
 
+/// Result of a tool call requested by the model
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolMessage {
+    /// The `ToolCall::id` this result answers
+    pub id: String,
+    /// Name of the function that was called
+    pub name: String,
+    /// The function's output
+    pub content: ToolResultContent,
+}
+
+/// Output of a tool call, either structured JSON or plain text
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ToolResultContent {
+    Json(serde_json::Value),
+    Text(String),
+}
+
 /// Content of a message, which can be text or include tool calls
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MessageContent {
@@ -39,6 +66,23 @@ pub enum MessageContent {
         text: Option<String>,
         tool_calls: Vec<ToolCall>,
     },
+    /// Multimodal content: text, inline binary data and file references, in order
+    Parts(Vec<ContentPart>),
+}
+
+/// A single piece of multimodal message content
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ContentPart {
+    /// Plain text
+    Text(String),
+    /// Binary data sent inline with the request, e.g. an image or a PDF
+    InlineData {
+        mime_type: String,
+        /// Base64-encoded bytes
+        data: String,
+    },
+    /// A file already uploaded to the provider, referenced by URI
+    FileData { mime_type: String, file_uri: String },
 }
 
 impl MessageContent {
@@ -55,11 +99,19 @@ impl MessageContent {
         }
     }
 
-    /// Get the text content, if any
+    /// Create multimodal content from parts
+    pub fn parts(parts: Vec<ContentPart>) -> Self {
+        Self::Parts(parts)
+    }
+
+    /// Get the text content, if any.
+    ///
+    /// Returns the first text part of multimodal content.
     pub fn as_text(&self) -> Option<&str> {
         match self {
             Self::Text(text) => Some(text),
             Self::Mixed { text, .. } => text.as_deref(),
+            Self::Parts(parts) => parts.iter().find_map(ContentPart::as_text),
         }
     }
 
@@ -68,9 +120,19 @@ impl MessageContent {
         match self {
             Self::Text(text) => text.clone(),
             Self::Mixed { text, .. } => text.clone().unwrap_or_default(),
+            Self::Parts(parts) => parts.iter().filter_map(ContentPart::as_text).collect(),
         }
     }
 
+    /// Get the parts other than text, such as images and files
+    pub fn media_parts(&self) -> impl Iterator<Item = &ContentPart> {
+        let parts: &[ContentPart] = match self {
+            Self::Parts(parts) => parts,
+            _ => &[],
+        };
+        parts.iter().filter(|part| part.as_text().is_none())
+    }
+
     /// Check if this content has tool calls
     pub fn has_tool_calls(&self) -> bool {
         matches!(self, Self::Mixed { tool_calls, .. } if !tool_calls.is_empty())
@@ -79,8 +141,8 @@ impl MessageContent {
     /// Get tool calls, if any
     pub fn tool_calls(&self) -> &[ToolCall] {
         match self {
-            Self::Text(_) => &[],
             Self::Mixed { tool_calls, .. } => tool_calls,
+            _ => &[],
         }
     }
 }
@@ -112,6 +174,45 @@ impl Message {
         })
     }
 
+    /// Create a new human message with multimodal content
+    pub fn human_with_parts(parts: Vec<ContentPart>) -> Self {
+        Self::Human(HumanMessage {
+            content: MessageContent::Parts(parts),
+        })
+    }
+
+    /// Create a new human message with text followed by an image read from `path`.
+    ///
+    /// The image's MIME type is detected from its contents, falling back to the file
+    /// extension; files that are not images are rejected.
+    pub fn human_with_image<S: Into<String>, P: AsRef<Path>>(text: S, path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let image = ContentPart::from_file(path)?;
+
+        match image.mime_type() {
+            Some(mime_type) if mime_type.starts_with("image/") => {
+                Ok(Self::human_with_parts(vec![ContentPart::text(text), image]))
+            }
+            mime_type => Err(OrchestraError::invalid_request(format!(
+                "{} is not an image ({})",
+                path.display(),
+                mime_type.unwrap_or("unknown type")
+            ))),
+        }
+    }
+
+    /// Create a new human message with text followed by a local file (image, audio,
+    /// video, PDF, ...) sent inline.
+    ///
+    /// The file's MIME type is detected from its contents, falling back to the file
+    /// extension.
+    pub fn human_with_file<S: Into<String>, P: AsRef<Path>>(text: S, path: P) -> Result<Self> {
+        Ok(Self::human_with_parts(vec![
+            ContentPart::text(text),
+            ContentPart::from_file(path)?,
+        ]))
+    }
+
     /// Create a new system message
     pub fn system<S: Into<String>>(content: S) -> Self {
         Self::System(SystemMessage {
@@ -119,12 +220,28 @@ impl Message {
         })
     }
 
+    /// Create a new assistant message carrying the tool calls the model requested
+    pub fn assistant_tool_calls<S: Into<String>>(text: Option<S>, tool_calls: Vec<ToolCall>) -> Self {
+        Self::Assistant(AssistantMessage::with_tool_calls(text, tool_calls))
+    }
+
+    /// Create a new message sending the result of a tool call back to the model
+    pub fn tool<I, N, C>(id: I, name: N, content: C) -> Self
+    where
+        I: Into<String>,
+        N: Into<String>,
+        C: Into<ToolResultContent>,
+    {
+        Self::Tool(ToolMessage::new(id, name, content))
+    }
+
     /// Get the role of this message as a string
     pub fn role(&self) -> &'static str {
         match self {
             Self::Human(_) => "user",
             Self::Assistant(_) => "assistant",
             Self::System(_) => "system",
+            Self::Tool(_) => "tool",
         }
     }
 
@@ -134,6 +251,7 @@ impl Message {
             Self::Human(msg) => msg.content.to_text(),
             Self::Assistant(msg) => msg.content.to_text(),
             Self::System(msg) => msg.content.clone(),
+            Self::Tool(msg) => msg.content.to_text(),
         }
     }
 }
@@ -179,6 +297,113 @@ impl SystemMessage {
     }
 }
 
+impl ContentPart {
+    /// Create a text part
+    pub fn text<S: Into<String>>(text: S) -> Self {
+        Self::Text(text.into())
+    }
+
+    /// Create an inline data part, base64-encoding `bytes`
+    pub fn inline_data<M: Into<String>>(mime_type: M, bytes: &[u8]) -> Self {
+        Self::InlineData {
+            mime_type: mime_type.into(),
+            data: BASE64.encode(bytes),
+        }
+    }
+
+    /// Create a part referencing an uploaded file by URI
+    pub fn file_uri<M: Into<String>, U: Into<String>>(mime_type: M, file_uri: U) -> Self {
+        Self::FileData {
+            mime_type: mime_type.into(),
+            file_uri: file_uri.into(),
+        }
+    }
+
+    /// Read a local file into an inline data part, detecting its MIME type.
+    ///
+    /// Returns [`OrchestraError::InvalidRequest`] if the MIME type cannot be detected.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        let mime_type = media::detect_mime_type(path, &bytes).ok_or_else(|| {
+            OrchestraError::invalid_request(format!(
+                "Could not detect the MIME type of {}",
+                path.display()
+            ))
+        })?;
+
+        Ok(Self::inline_data(mime_type, &bytes))
+    }
+
+    /// Get the text of this part, if it is a text part
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Get the MIME type of this part, if it carries data or a file
+    pub fn mime_type(&self) -> Option<&str> {
+        match self {
+            Self::Text(_) => None,
+            Self::InlineData { mime_type, .. } | Self::FileData { mime_type, .. } => Some(mime_type),
+        }
+    }
+}
+
+impl ToolMessage {
+    /// Create a new tool result message
+    pub fn new<I, N, C>(id: I, name: N, content: C) -> Self
+    where
+        I: Into<String>,
+        N: Into<String>,
+        C: Into<ToolResultContent>,
+    {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            content: content.into(),
+        }
+    }
+}
+
+impl ToolResultContent {
+    /// Get the result as text; JSON results are serialized
+    pub fn to_text(&self) -> String {
+        match self {
+            Self::Json(value) => value.to_string(),
+            Self::Text(text) => text.clone(),
+        }
+    }
+
+    /// Get the result as JSON; text results become a JSON string
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::Json(value) => value.clone(),
+            Self::Text(text) => serde_json::Value::String(text.clone()),
+        }
+    }
+}
+
+impl From<serde_json::Value> for ToolResultContent {
+    fn from(value: serde_json::Value) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl From<String> for ToolResultContent {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for ToolResultContent {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -270,6 +495,76 @@ mod tests {
         assert!(msg_with_tools.content.has_tool_calls());
     }
 
+    #[test]
+    fn test_tool_message() {
+        let msg = Message::tool("call_1", "get_weather", serde_json::json!({"temperature": 21}));
+        assert_eq!(msg.role(), "tool");
+        assert_eq!(msg.content_text(), r#"{"temperature":21}"#);
+        match msg {
+            Message::Tool(tool_msg) => {
+                assert_eq!(tool_msg.id, "call_1");
+                assert_eq!(tool_msg.name, "get_weather");
+                assert_eq!(
+                    tool_msg.content,
+                    ToolResultContent::Json(serde_json::json!({"temperature": 21}))
+                );
+            }
+            _ => panic!("Expected tool message"),
+        }
+
+        let msg = ToolMessage::new("call_2", "lookup", "not found");
+        assert_eq!(msg.content.to_text(), "not found");
+        assert_eq!(msg.content.to_json(), serde_json::json!("not found"));
+    }
+
+    #[test]
+    fn test_multimodal_message() {
+        let path = std::env::temp_dir().join(format!("orchestra-test-{}.bin", std::process::id()));
+        std::fs::write(&path, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+
+        let msg = Message::human_with_image("What is in this picture?", &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(msg.content_text(), "What is in this picture?");
+        match msg {
+            Message::Human(HumanMessage {
+                content: MessageContent::Parts(parts),
+            }) => {
+                assert_eq!(parts.len(), 2);
+                assert_eq!(
+                    parts[1],
+                    ContentPart::InlineData {
+                        mime_type: "image/png".to_string(),
+                        data: "iVBORw0KGgoAAAANSUhEUg==".to_string(),
+                    }
+                );
+            }
+            other => panic!("Expected multimodal human message, got {:?}", other),
+        }
+
+        assert!(matches!(
+            Message::human_with_image("Hi", "/does/not/exist.png"),
+            Err(OrchestraError::Io(_))
+        ));
+
+        let path = std::env::temp_dir().join(format!("orchestra-test-{}.pdf", std::process::id()));
+        std::fs::write(&path, b"%PDF-1.7").unwrap();
+        let result = Message::human_with_image("Hi", &path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(OrchestraError::InvalidRequest { .. })));
+    }
+
+    #[test]
+    fn test_from_file_unknown_mime_type() {
+        let path = std::env::temp_dir().join(format!("orchestra-{}.unknown", std::process::id()));
+        std::fs::write(&path, b"no known signature").unwrap();
+
+        let result = ContentPart::from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(OrchestraError::InvalidRequest { .. })));
+    }
+
     #[test]
     fn test_message_serialization() {
         let msg = Message::human("Test message");
@@ -311,3 +606,21 @@ pub struct ToolFunction {
     pub name: String,
     pub arguments: serde_json::Value,
 }
+
+impl ToolFunction {
+    /// Deserialize the arguments chosen by the model into `T`.
+    ///
+    /// Returns [`OrchestraError::InvalidToolArguments`], naming this function, if they
+    /// do not match.
+    pub fn parse_arguments<T: crate::tools::ToolArgs>(&self) -> Result<T> {
+        T::from_arguments(&self.arguments).map_err(|e| match e {
+            OrchestraError::InvalidToolArguments { message, source } => {
+                OrchestraError::InvalidToolArguments {
+                    message: format!("{}: {}", self.name, message),
+                    source,
+                }
+            }
+            other => other,
+        })
+    }
+}
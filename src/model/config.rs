@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
-use crate::error::{OrchestraError, Result};
+use serde_json::Value;
+use crate::{
+    error::{OrchestraError, Result},
+    model::{HarmBlockThreshold, HarmCategory, SafetySetting},
+    tools::{ToolDefinition, ToolSchema},
+};
 
 /// Configuration for a language model
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,7 +16,28 @@ pub struct ModelConfig {
     pub top_k: Option<u32>,
     pub max_tokens: Option<u32>,
     pub thinking_mode: Option<bool>,
+    /// Maximum number of tokens the model may spend thinking, for models that support it
+    #[serde(default)]
+    pub thinking_budget: Option<u32>,
+    /// Whether to return the model's thought summaries
+    #[serde(default)]
+    pub include_thoughts: Option<bool>,
     pub stop_sequences: Vec<String>,
+    /// Number of alternative responses to generate, for providers that support it
+    #[serde(default)]
+    pub candidate_count: Option<u32>,
+    /// MIME type the reply must be in, e.g. `application/json`
+    #[serde(default)]
+    pub response_mime_type: Option<String>,
+    /// JSON schema the reply must conform to; requires a JSON `response_mime_type`
+    #[serde(default)]
+    pub response_schema: Option<Value>,
+    /// Per-category safety filter thresholds, for providers that support them
+    #[serde(default)]
+    pub safety_settings: Vec<SafetySetting>,
+    /// Tools the model may call
+    #[serde(default)]
+    pub tools: Vec<ToolDefinition>,
 }
 
 impl Default for ModelConfig {
@@ -20,16 +46,28 @@ impl Default for ModelConfig {
             name: String::new(),
             system_instruction: None,
             temperature: 1.0,
-            top_p: 0.95,
+            top_p: Self::DEFAULT_TOP_P,
             top_k: None,
             max_tokens: None,
             thinking_mode: None,
+            thinking_budget: None,
+            include_thoughts: None,
             stop_sequences: Vec::new(),
+            candidate_count: None,
+            response_mime_type: None,
+            response_schema: None,
+            safety_settings: Vec::new(),
+            tools: Vec::new(),
         }
     }
 }
 
 impl ModelConfig {
+    pub const JSON_MIME_TYPE: &str = "application/json";
+
+    /// `top_p` used unless another value is set
+    pub const DEFAULT_TOP_P: f32 = 0.95;
+
     /// Create a new model configuration with the given model name
     pub fn new<S: Into<String>>(name: S) -> Self {
         Self {
@@ -86,6 +124,18 @@ impl ModelConfig {
         self
     }
 
+    /// Limit the number of tokens spent thinking; `0` turns thinking off where allowed
+    pub fn with_thinking_budget(mut self, thinking_budget: u32) -> Self {
+        self.thinking_budget = Some(thinking_budget);
+        self
+    }
+
+    /// Request the model's thought summaries alongside its reply
+    pub fn with_include_thoughts(mut self, include_thoughts: bool) -> Self {
+        self.include_thoughts = Some(include_thoughts);
+        self
+    }
+
     /// Add a stop sequence
     pub fn with_stop_sequence<S: Into<String>>(mut self, stop_sequence: S) -> Self {
         self.stop_sequences.push(stop_sequence.into());
@@ -102,6 +152,64 @@ impl ModelConfig {
         self
     }
 
+    /// Set the number of alternative responses to generate (at least 1)
+    pub fn with_candidate_count(mut self, candidate_count: u32) -> Result<Self> {
+        if candidate_count == 0 {
+            return Err(OrchestraError::config("candidate_count must be greater than 0"));
+        }
+        self.candidate_count = Some(candidate_count);
+        Ok(self)
+    }
+
+    /// Set the MIME type of the reply, e.g. `application/json` for JSON mode
+    pub fn with_response_mime_type<S: Into<String>>(mut self, mime_type: S) -> Self {
+        self.response_mime_type = Some(mime_type.into());
+        self
+    }
+
+    /// Constrain the reply to a JSON schema. This also switches the reply to JSON.
+    pub fn with_response_schema(mut self, schema: Value) -> Self {
+        self.response_mime_type = Some(Self::JSON_MIME_TYPE.to_string());
+        self.response_schema = Some(schema);
+        self
+    }
+
+    /// Constrain the reply to the schema of `T`, e.g. a type deriving `ToolSchema`
+    pub fn with_response_type<T: ToolSchema>(self) -> Self {
+        self.with_response_schema(T::json_schema())
+    }
+
+    /// Whether the reply was requested as JSON
+    pub fn wants_json(&self) -> bool {
+        self.response_mime_type.as_deref() == Some(Self::JSON_MIME_TYPE)
+    }
+
+    /// Set the blocking threshold for a harm category, replacing any earlier setting for it
+    pub fn with_safety_setting(
+        mut self,
+        category: HarmCategory,
+        threshold: HarmBlockThreshold,
+    ) -> Self {
+        self.safety_settings.retain(|setting| setting.category != category);
+        self.safety_settings.push(SafetySetting::new(category, threshold));
+        self
+    }
+
+    /// Add a tool the model may call
+    pub fn with_tool(mut self, tool: ToolDefinition) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    /// Set the tools the model may call
+    pub fn with_tools<I>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = ToolDefinition>,
+    {
+        self.tools = tools.into_iter().collect();
+        self
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> Result<()> {
         if self.name.is_empty() {
@@ -116,10 +224,24 @@ impl ModelConfig {
             return Err(OrchestraError::config("top_p must be between 0.0 and 1.0"));
         }
 
-        if let Some(max_tokens) = self.max_tokens {
-            if max_tokens == 0 {
-                return Err(OrchestraError::config("max_tokens must be greater than 0"));
-            }
+        if self.max_tokens == Some(0) {
+            return Err(OrchestraError::config("max_tokens must be greater than 0"));
+        }
+
+        if self.candidate_count == Some(0) {
+            return Err(OrchestraError::config("candidate_count must be greater than 0"));
+        }
+
+        if self.thinking_mode == Some(false) && self.include_thoughts == Some(true) {
+            return Err(OrchestraError::config(
+                "include_thoughts requires thinking mode to be enabled",
+            ));
+        }
+
+        if self.response_schema.is_some() && !self.wants_json() {
+            return Err(OrchestraError::config(
+                "response_schema requires response_mime_type to be application/json",
+            ));
         }
 
         Ok(())
@@ -168,6 +290,8 @@ mod tests {
         assert!(config.max_tokens.is_none());
         assert!(config.thinking_mode.is_none());
         assert!(config.stop_sequences.is_empty());
+        assert!(config.candidate_count.is_none());
+        assert!(config.tools.is_empty());
     }
 
     #[test]
@@ -222,6 +346,11 @@ mod tests {
         config = ModelConfig::new("test");
         config.max_tokens = Some(0);
         assert!(config.validate().is_err());
+
+        // Test invalid candidate_count
+        config = ModelConfig::new("test");
+        config.candidate_count = Some(0);
+        assert!(config.validate().is_err());
     }
 
     #[test]
@@ -267,6 +396,52 @@ mod tests {
         assert_eq!(balanced.top_p, 0.9);
     }
 
+    #[test]
+    fn test_model_config_candidate_count() {
+        let config = ModelConfig::new("test").with_candidate_count(3).unwrap();
+        assert_eq!(config.candidate_count, Some(3));
+
+        assert!(ModelConfig::new("test").with_candidate_count(0).is_err());
+    }
+
+    #[test]
+    fn test_model_config_response_schema() {
+        let schema = serde_json::json!({"type": "object", "properties": {"name": {"type": "string"}}});
+        let config = ModelConfig::new("test").with_response_schema(schema.clone());
+        assert_eq!(config.response_mime_type.as_deref(), Some("application/json"));
+        assert_eq!(config.response_schema, Some(schema));
+        assert!(config.validate().is_ok());
+
+        // A schema cannot be combined with a non-JSON MIME type
+        let config = config.with_response_mime_type("text/plain");
+        assert!(!config.wants_json());
+        assert!(config.validate().is_err());
+
+        let config = ModelConfig::new("test").with_response_type::<Vec<String>>();
+        assert_eq!(
+            config.response_schema,
+            Some(serde_json::json!({"type": "array", "items": {"type": "string"}}))
+        );
+    }
+
+    #[test]
+    fn test_model_config_tools() {
+        let weather = ToolDefinition::new(
+            "get_weather",
+            "Get the weather",
+            serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        );
+        let time = ToolDefinition::without_parameters("get_time", "Get the current time");
+
+        let config = ModelConfig::new("test")
+            .with_tool(weather.clone())
+            .with_tool(time.clone());
+        assert_eq!(config.tools, vec![weather.clone(), time]);
+
+        let config = config.with_tools(vec![weather.clone()]);
+        assert_eq!(config.tools, vec![weather]);
+    }
+
     #[test]
     fn test_model_config_stop_sequences() {
         let config = ModelConfig::new("test")
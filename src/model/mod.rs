@@ -1,3 +1,5 @@
 mod config;
+mod safety;
 
 pub use config::ModelConfig;
+pub use safety::{HarmBlockThreshold, HarmCategory, SafetySetting};
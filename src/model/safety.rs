@@ -0,0 +1,50 @@
+//! Safety filter settings.
+
+use serde::{Deserialize, Serialize};
+
+/// A category of harmful content that providers can filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HarmCategory {
+    #[serde(rename = "HARM_CATEGORY_HARASSMENT")]
+    Harassment,
+    #[serde(rename = "HARM_CATEGORY_HATE_SPEECH")]
+    HateSpeech,
+    #[serde(rename = "HARM_CATEGORY_SEXUALLY_EXPLICIT")]
+    SexuallyExplicit,
+    #[serde(rename = "HARM_CATEGORY_DANGEROUS_CONTENT")]
+    DangerousContent,
+    #[serde(rename = "HARM_CATEGORY_CIVIC_INTEGRITY")]
+    CivicIntegrity,
+}
+
+/// The probability of harm at which content is blocked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HarmBlockThreshold {
+    /// Block content with a low, medium or high probability of harm
+    BlockLowAndAbove,
+    /// Block content with a medium or high probability of harm
+    BlockMediumAndAbove,
+    /// Block only content with a high probability of harm
+    BlockOnlyHigh,
+    /// Never block, but still report safety ratings
+    BlockNone,
+    /// Turn the filter off entirely
+    Off,
+}
+
+/// The blocking threshold for one harm category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetySetting {
+    pub category: HarmCategory,
+    pub threshold: HarmBlockThreshold,
+}
+
+impl SafetySetting {
+    pub fn new(category: HarmCategory, threshold: HarmBlockThreshold) -> Self {
+        Self {
+            category,
+            threshold,
+        }
+    }
+}
@@ -0,0 +1,516 @@
+//! # Output parsers
+//!
+//! Parsers turn the text of a model's reply into a typed value.
+//!
+//! An [`OutputParser`] parses the text and explains, through
+//! [`format_instructions`](OutputParser::format_instructions), the format it expects.
+//! [`LLM::chat_with_parser`](crate::llm::LLM::chat_with_parser) uses both: when a reply
+//! fails to parse, the error and instructions are sent back to the model and it is
+//! asked to try again.
+//!
+//! Built-in parsers:
+//!
+//! - [`JsonParser`] deserializes a JSON reply into any `DeserializeOwned` type
+//! - [`ChoiceParser`] accepts one of a fixed set of answers
+//! - [`ListParser`] splits a reply into items, one per line or by a separator
+//! - [`RegexParser`] extracts the named groups of a regular expression
+//!
+//! ## Examples
+//!
+//! ```rust,no_run
+//! use orchestra_rs::{llm::LLM, messages::Message, parsers::ChoiceParser};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let llm = LLM::gemini("gemini-2.5-flash");
+//!     let parser = ChoiceParser::new(["positive", "negative", "neutral"]);
+//!
+//!     let sentiment = llm
+//!         .chat_with_parser(Message::human("Sentiment of: 'I love it!'"), vec![], &parser, 2)
+//!         .await?;
+//!     println!("Sentiment: {}", sentiment);
+//!     Ok(())
+//! }
+//! ```
+
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use regex::Regex;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    error::{OrchestraError, Result},
+    model::ModelConfig,
+};
+
+/// Parses the text of a model's reply into a typed value.
+pub trait OutputParser: Send + Sync {
+    /// The parsed value
+    type Output;
+
+    /// Parse a reply.
+    ///
+    /// Failures should be reported as [`OrchestraError::InvalidOutput`], whose message
+    /// is shown to the model when it is asked to correct its reply.
+    fn parse(&self, text: &str) -> Result<Self::Output>;
+
+    /// A description of the expected format, sent to the model when a reply fails to parse
+    fn format_instructions(&self) -> Option<String> {
+        None
+    }
+
+    /// Adjust the request configuration, e.g. to switch the provider to JSON output
+    fn configure(&self, _config: &mut ModelConfig) {}
+}
+
+/// Deserializes a JSON reply into `T`.
+///
+/// A reply wrapped in a Markdown code fence is accepted. JSON output is requested from
+/// the provider only when `T` is read from a JSON object, since OpenAI's JSON mode
+/// rejects any other top-level value, and not when the configuration already sets a
+/// response MIME type or declares tools, which Gemini does not allow together with JSON
+/// output. The format instructions ask for JSON either way.
+pub struct JsonParser<T> {
+    _output: PhantomData<fn() -> T>,
+}
+
+impl<T> JsonParser<T> {
+    pub fn new() -> Self {
+        Self {
+            _output: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for JsonParser<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::fmt::Debug for JsonParser<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonParser")
+            .field("output", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T: DeserializeOwned> OutputParser for JsonParser<T> {
+    type Output = T;
+
+    fn parse(&self, text: &str) -> Result<T> {
+        serde_json::from_str(strip_code_fence(text)).map_err(|e| OrchestraError::InvalidOutput {
+            expected: std::any::type_name::<T>().to_string(),
+            message: e.to_string(),
+            output: text.to_string(),
+            source: Some(e),
+        })
+    }
+
+    fn format_instructions(&self) -> Option<String> {
+        Some("Respond with a single valid JSON value and nothing else.".to_string())
+    }
+
+    fn configure(&self, config: &mut ModelConfig) {
+        if config.response_mime_type.is_none()
+            && config.tools.is_empty()
+            && deserializes_from_object::<T>()
+        {
+            config.response_mime_type = Some(ModelConfig::JSON_MIME_TYPE.to_string());
+        }
+    }
+}
+
+/// Accepts exactly one of a fixed set of answers.
+///
+/// Matching ignores case, surrounding whitespace, quotes and trailing punctuation, and
+/// the choice is returned as it was given to [`ChoiceParser::new`].
+#[derive(Debug, Clone)]
+pub struct ChoiceParser {
+    choices: Vec<String>,
+}
+
+impl ChoiceParser {
+    pub fn new<I, S>(choices: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            choices: choices.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// The accepted answers
+    pub fn choices(&self) -> &[String] {
+        &self.choices
+    }
+}
+
+impl OutputParser for ChoiceParser {
+    type Output = String;
+
+    fn parse(&self, text: &str) -> Result<String> {
+        let answer = text
+            .trim()
+            .trim_matches(|c: char| matches!(c, '"' | '\'' | '`' | '*'))
+            .trim_end_matches(['.', '!'])
+            .trim();
+
+        self.choices
+            .iter()
+            .find(|choice| choice.eq_ignore_ascii_case(answer))
+            .cloned()
+            .ok_or_else(|| {
+                OrchestraError::invalid_output(
+                    "choice",
+                    format!("expected one of: {}", self.choices.join(", ")),
+                    text,
+                )
+            })
+    }
+
+    fn format_instructions(&self) -> Option<String> {
+        Some(format!(
+            "Respond with exactly one of the following and nothing else: {}.",
+            self.choices.join(", ")
+        ))
+    }
+}
+
+/// Splits a reply into a list of items.
+///
+/// By default every non-empty line is an item, with bullets (`-`, `*`, `•`) and
+/// numbering (`1.`, `1)`) removed. Use [`ListParser::with_separator`] for replies such
+/// as `red, green, blue`.
+#[derive(Debug, Clone, Default)]
+pub struct ListParser {
+    separator: Option<String>,
+}
+
+impl ListParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Split on `separator` instead of on lines
+    pub fn with_separator<S: Into<String>>(mut self, separator: S) -> Self {
+        self.separator = Some(separator.into());
+        self
+    }
+
+    fn strip_marker(item: &str) -> &str {
+        let item = item.trim();
+        if let Some(rest) = item
+            .strip_prefix(['-', '*', '•'])
+            .filter(|rest| rest.starts_with(' '))
+        {
+            return rest.trim();
+        }
+
+        let digits = item.len() - item.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits > 0
+            && let Some(rest) = item[digits..].strip_prefix(['.', ')'])
+        {
+            return rest.trim();
+        }
+
+        item
+    }
+}
+
+impl OutputParser for ListParser {
+    type Output = Vec<String>;
+
+    fn parse(&self, text: &str) -> Result<Vec<String>> {
+        let text_items: Vec<&str> = match &self.separator {
+            Some(separator) => text.split(separator.as_str()).collect(),
+            None => text.lines().collect(),
+        };
+
+        let items: Vec<String> = text_items
+            .into_iter()
+            .map(Self::strip_marker)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        if items.is_empty() {
+            return Err(OrchestraError::invalid_output("list", "the reply contains no items", text));
+        }
+
+        Ok(items)
+    }
+
+    fn format_instructions(&self) -> Option<String> {
+        Some(match &self.separator {
+            Some(separator) => format!(
+                "Respond with the items separated by \"{}\" and nothing else.",
+                separator
+            ),
+            None => "Respond with one item per line and nothing else.".to_string(),
+        })
+    }
+}
+
+/// Extracts the named capture groups of a regular expression from a reply.
+///
+/// The first match is used; optional groups that did not participate are left out.
+#[derive(Debug, Clone)]
+pub struct RegexParser {
+    regex: Regex,
+    instructions: Option<String>,
+}
+
+impl RegexParser {
+    /// Create a parser from a pattern with at least one named group, e.g.
+    /// `Answer: (?P<answer>\w+)`
+    pub fn new(pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| OrchestraError::config(format!("Invalid output pattern: {}", e)))?;
+
+        if regex.capture_names().flatten().next().is_none() {
+            return Err(OrchestraError::config(
+                "Output pattern must contain at least one named group",
+            ));
+        }
+
+        Ok(Self {
+            regex,
+            instructions: None,
+        })
+    }
+
+    /// Describe the expected format to the model, since it can't be derived from the pattern
+    pub fn with_format_instructions<S: Into<String>>(mut self, instructions: S) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+}
+
+impl OutputParser for RegexParser {
+    type Output = BTreeMap<String, String>;
+
+    fn parse(&self, text: &str) -> Result<BTreeMap<String, String>> {
+        let captures = self.regex.captures(text).ok_or_else(|| {
+            OrchestraError::invalid_output(
+                "pattern",
+                format!("the reply does not match `{}`", self.regex.as_str()),
+                text,
+            )
+        })?;
+
+        Ok(self
+            .regex
+            .capture_names()
+            .flatten()
+            .filter_map(|name| {
+                captures
+                    .name(name)
+                    .map(|value| (name.to_string(), value.as_str().to_string()))
+            })
+            .collect())
+    }
+
+    fn format_instructions(&self) -> Option<String> {
+        self.instructions.clone()
+    }
+}
+
+/// Whether `T` is read from a JSON object, found by asking its `Deserialize` impl which
+/// shape it expects. Types that accept any value, such as `serde_json::Value`, count as
+/// not being objects.
+fn deserializes_from_object<T: DeserializeOwned>() -> bool {
+    use serde::de::{self, Deserializer, Visitor};
+
+    /// A deserializer that stops at the first shape `T` asks for
+    struct ShapeProbe;
+
+    /// Ends the probe, recording whether an object was asked for
+    #[derive(Debug)]
+    struct Shape {
+        object: bool,
+    }
+
+    impl std::fmt::Display for Shape {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "expects an object: {}", self.object)
+        }
+    }
+
+    impl std::error::Error for Shape {}
+
+    impl de::Error for Shape {
+        fn custom<M: std::fmt::Display>(_msg: M) -> Self {
+            Shape { object: false }
+        }
+    }
+
+    impl<'de> Deserializer<'de> for ShapeProbe {
+        type Error = Shape;
+
+        fn deserialize_any<V: Visitor<'de>>(
+            self,
+            _visitor: V,
+        ) -> std::result::Result<V::Value, Shape> {
+            Err(Shape { object: false })
+        }
+
+        fn deserialize_map<V: Visitor<'de>>(
+            self,
+            _visitor: V,
+        ) -> std::result::Result<V::Value, Shape> {
+            Err(Shape { object: true })
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            _fields: &'static [&'static str],
+            _visitor: V,
+        ) -> std::result::Result<V::Value, Shape> {
+            Err(Shape { object: true })
+        }
+
+        // Look through wrappers that are transparent in JSON
+        fn deserialize_option<V: Visitor<'de>>(
+            self,
+            visitor: V,
+        ) -> std::result::Result<V::Value, Shape> {
+            visitor.visit_some(self)
+        }
+
+        fn deserialize_newtype_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            visitor: V,
+        ) -> std::result::Result<V::Value, Shape> {
+            visitor.visit_newtype_struct(self)
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+            byte_buf unit unit_struct seq tuple tuple_struct enum identifier ignored_any
+        }
+    }
+
+    matches!(T::deserialize(ShapeProbe), Err(Shape { object: true }))
+}
+
+/// The contents of a reply wrapped in a Markdown code fence, or the trimmed reply
+fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        // Drop the info string, e.g. the `json` in ```json
+        .map(|fenced| match fenced.split_once('\n') {
+            Some((_, body)) => body,
+            // On a single line the info string runs into the body, as in ```json{..}```
+            None => {
+                let body = fenced.trim_start_matches(|c: char| c.is_ascii_alphanumeric());
+                if body.trim().is_empty() { fenced } else { body }
+            }
+        })
+        .unwrap_or(trimmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_parser() {
+        #[derive(Debug, serde::Deserialize, PartialEq)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        let parser = JsonParser::<Point>::new();
+        assert_eq!(parser.parse(r#"{"x": 1, "y": 2}"#).unwrap(), Point { x: 1, y: 2 });
+        assert_eq!(
+            parser.parse("```json\n{\"x\": 3, \"y\": 4}\n```").unwrap(),
+            Point { x: 3, y: 4 }
+        );
+        assert_eq!(
+            parser.parse("```json{\"x\": 5, \"y\": 6}```").unwrap(),
+            Point { x: 5, y: 6 }
+        );
+        assert_eq!(parser.parse("```{\"x\": 7, \"y\": 8}```").unwrap(), Point { x: 7, y: 8 });
+        assert_eq!(JsonParser::<u32>::new().parse("```42```").unwrap(), 42);
+        assert!(matches!(
+            parser.parse("x = 1, y = 2"),
+            Err(OrchestraError::InvalidOutput { .. })
+        ));
+
+        let mut config = ModelConfig::new("test");
+        parser.configure(&mut config);
+        assert!(config.wants_json());
+
+        // JSON output cannot be combined with function calling
+        let mut config = ModelConfig::new("test").with_tool(crate::tools::ToolDefinition::new(
+            "lookup",
+            "Look something up",
+            serde_json::json!({"type": "object"}),
+        ));
+        parser.configure(&mut config);
+        assert!(config.response_mime_type.is_none());
+
+        // OpenAI's JSON mode only allows a top-level object
+        let mut config = ModelConfig::new("test");
+        JsonParser::<Vec<Point>>::new().configure(&mut config);
+        assert!(config.response_mime_type.is_none());
+        JsonParser::<u32>::new().configure(&mut config);
+        assert!(config.response_mime_type.is_none());
+        JsonParser::<Option<BTreeMap<String, u32>>>::new().configure(&mut config);
+        assert!(config.wants_json());
+    }
+
+    #[test]
+    fn test_choice_parser() {
+        let parser = ChoiceParser::new(["Positive", "Negative"]);
+
+        assert_eq!(parser.parse("positive").unwrap(), "Positive");
+        assert_eq!(parser.parse(" \"NEGATIVE.\"\n").unwrap(), "Negative");
+
+        match parser.parse("Mixed") {
+            Err(OrchestraError::InvalidOutput { message, .. }) => {
+                assert_eq!(message, "expected one of: Positive, Negative");
+            }
+            other => panic!("Expected invalid output error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_list_parser() {
+        let parser = ListParser::new();
+        assert_eq!(
+            parser.parse("1. Paris\n2) Rome\n\n- Madrid\n* Berlin\n-5 degrees").unwrap(),
+            vec!["Paris", "Rome", "Madrid", "Berlin", "-5 degrees"]
+        );
+
+        let parser = ListParser::new().with_separator(",");
+        assert_eq!(parser.parse("red, green ,blue").unwrap(), vec!["red", "green", "blue"]);
+        assert!(parser.parse(" , ").is_err());
+    }
+
+    #[test]
+    fn test_regex_parser() {
+        let parser = RegexParser::new(r"Answer: (?P<answer>\w+)(?: \((?P<confidence>\d+)%\))?").unwrap();
+
+        let captures = parser.parse("Let me think.\nAnswer: Paris (90%)").unwrap();
+        assert_eq!(captures["answer"], "Paris");
+        assert_eq!(captures["confidence"], "90");
+
+        let captures = parser.parse("Answer: Rome").unwrap();
+        assert!(!captures.contains_key("confidence"));
+
+        assert!(parser.parse("I don't know").is_err());
+        assert!(matches!(
+            RegexParser::new(r"Answer: (\w+)"),
+            Err(OrchestraError::Config { .. })
+        ));
+    }
+}
@@ -0,0 +1,382 @@
+use crate::{
+    error::{OrchestraError, Result},
+    messages::Message,
+    model::ModelConfig,
+    providers::{
+        Provider,
+        config::AnthropicConfig,
+        http::{HttpClient, model_error_from_response},
+        retry::RetryPolicy,
+        types::{Candidate, ChatResponse, ResponsePart, Usage},
+    },
+};
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName};
+
+use super::types::{
+    AnthropicChatResponse, AnthropicErrorResponse, AnthropicRequestBody, PREDEFINED_MODELS,
+};
+
+#[derive(Debug)]
+pub struct AnthropicProvider {
+    config: AnthropicConfig,
+    base_url: String,
+    http: HttpClient,
+}
+
+impl AnthropicProvider {
+    pub const DEFAULT_API_KEY_ENV: &str = "ANTHROPIC_API_KEY";
+
+    /// Create a new AnthropicProvider with default configuration
+    pub fn with_default_config() -> Self {
+        Self::new(AnthropicConfig::default())
+    }
+
+    /// Build the headers shared by all Anthropic requests
+    fn build_headers(&self) -> Result<HeaderMap> {
+        let api_key = self.config.get_api_key().ok_or_else(|| {
+            OrchestraError::api_key("API key not found in configuration or environment")
+        })?;
+
+        let mut headers = HeaderMap::new();
+
+        headers.insert("x-api-key", api_key.parse()?);
+        headers.insert("anthropic-version", self.config.api_version.parse()?);
+        headers.insert("Content-Type", "application/json".parse()?);
+        for (key, value) in &self.config.base.headers {
+            let name = HeaderName::from_bytes(key.as_bytes())
+                .map_err(|e| OrchestraError::config(format!("Invalid header name {}: {}", key, e)))?;
+            headers.insert(name, value.parse()?);
+        }
+
+        Ok(headers)
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicProvider {
+    type Config = AnthropicConfig;
+
+    fn new(config: Self::Config) -> Self {
+        let base_url = config.get_base_url().trim_end_matches('/').to_string();
+        let http = HttpClient::new(&config.base);
+        Self {
+            config,
+            base_url,
+            http,
+        }
+    }
+
+    fn get_base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn get_predefined_models(&self) -> Result<Vec<String>> {
+        Ok(PREDEFINED_MODELS.iter().map(|s| s.to_string()).collect())
+    }
+
+    async fn prompt(&self, model_config: ModelConfig, prompt: String) -> Result<ChatResponse> {
+        self.chat(model_config, Message::human(prompt), vec![])
+            .await
+    }
+
+    async fn chat(
+        &self,
+        model_config: ModelConfig,
+        message: Message,
+        chat_history: Vec<Message>,
+    ) -> Result<ChatResponse> {
+        let client = self.http.get()?;
+
+        let headers = self.build_headers()?;
+
+        // Combine history + new_message
+        let mut messages_to_send = chat_history;
+        messages_to_send.push(message);
+
+        let request_url = format!("{}/messages", self.get_base_url());
+
+        let request_body = AnthropicRequestBody::from_model_config(
+            &model_config,
+            &messages_to_send,
+            self.config.default_max_tokens,
+        )?;
+
+        let request = client.post(request_url).headers(headers).json(&request_body);
+        let resp = RetryPolicy::from_config(&self.config.base)
+            .send(request, |resp| {
+                model_error_from_response("anthropic", resp, AnthropicErrorResponse::parse_message)
+            })
+            .await?;
+
+        let anthropic_response: AnthropicChatResponse = resp.json().await?;
+
+        let parts: Vec<ResponsePart> = anthropic_response
+            .content
+            .iter()
+            .filter_map(|block| match block.block_type.as_str() {
+                "text" => block.text.clone().map(ResponsePart::Text),
+                "thinking" => block.thinking.clone().map(ResponsePart::Thought),
+                _ => None,
+            })
+            .collect();
+
+        // The Messages API always generates a single response. Its text may be empty,
+        // e.g. when `max_tokens` ran out while thinking; the finish reason tells why
+        let candidate = Candidate {
+            finish_reason: anthropic_response.finish_reason(),
+            ..Candidate::from_parts(0, parts)
+        };
+
+        Ok(ChatResponse {
+            usage: anthropic_response.usage.as_ref().map(Usage::from),
+            model_version: anthropic_response.model,
+            response_id: anthropic_response.id,
+            ..ChatResponse::from_candidates(vec![candidate])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        messages::ContentPart,
+        providers::{
+            test_server::{MockResponse, TestServer, fixtures::anthropic_message},
+            types::FinishReason,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_chat_request_mapping() {
+        let server =
+            TestServer::start(vec![MockResponse::json(anthropic_message("Your name is Ayoub.", "end_turn"))])
+                .await;
+        let provider = AnthropicProvider::new(
+            AnthropicConfig::new()
+                .with_api_key("test-key")
+                .with_base_url(server.url())
+                .with_default_max_tokens(512)
+                .with_header("anthropic-beta", "output-128k-2025-02-19"),
+        );
+
+        let model_config = ModelConfig::new("claude-sonnet-4-0")
+            .with_system_instruction("Be concise.")
+            .with_stop_sequence("END");
+
+        let history = vec![
+            Message::system("The user's name is Ayoub."),
+            Message::human("Hi"),
+            Message::human("Remember my name"),
+            Message::assistant("Sure."),
+        ];
+
+        let resp = provider
+            .chat(model_config, Message::human("What is my name?"), history)
+            .await
+            .unwrap();
+
+        assert_eq!(resp.text, "Your name is Ayoub.");
+        assert_eq!(resp.finish_reason, Some(FinishReason::Stop));
+        assert_eq!(resp.usage.as_ref().map(|u| u.total_tokens), Some(28));
+        assert_eq!(resp.model_version.as_deref(), Some("claude-sonnet-4-0"));
+        assert_eq!(resp.response_id.as_deref(), Some("msg_01"));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/messages");
+        assert_eq!(requests[0].header("x-api-key"), Some("test-key"));
+        assert_eq!(requests[0].header("anthropic-version"), Some("2023-06-01"));
+        assert_eq!(requests[0].header("anthropic-beta"), Some("output-128k-2025-02-19"));
+
+        let body = requests[0].json();
+        assert_eq!(body["model"], "claude-sonnet-4-0");
+        assert_eq!(body["system"], "Be concise.\n\nThe user's name is Ayoub.");
+        assert_eq!(body["max_tokens"], 512);
+        assert_eq!(body["temperature"], 1.0);
+        assert_eq!(body["stop_sequences"], serde_json::json!(["END"]));
+        assert_eq!(
+            body["messages"],
+            serde_json::json!([
+                {"role": "user", "content": "Hi\n\nRemember my name"},
+                {"role": "assistant", "content": "Sure."},
+                {"role": "user", "content": "What is my name?"}
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_response_without_text() {
+        let server = TestServer::start(vec![MockResponse::json(serde_json::json!({
+            "id": "msg_02",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-0",
+            "content": [{"type": "thinking", "thinking": "Let me work through this", "signature": "sig"}],
+            "stop_reason": "max_tokens",
+            "usage": {"input_tokens": 20, "output_tokens": 64}
+        }))])
+        .await;
+        let provider = AnthropicProvider::new(
+            AnthropicConfig::new().with_api_key("test-key").with_base_url(server.url()),
+        );
+
+        let resp = provider
+            .prompt(ModelConfig::new("claude-sonnet-4-0"), "Hi".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(resp.text, "");
+        assert_eq!(resp.finish_reason, Some(FinishReason::MaxTokens));
+        assert_eq!(resp.thought_summary().as_deref(), Some("Let me work through this"));
+    }
+
+    #[test]
+    fn test_request_body_max_tokens_and_temperature() {
+        let config = ModelConfig::new("claude-3-5-haiku-latest").with_max_tokens(100);
+        let body =
+            AnthropicRequestBody::from_model_config(&config, &[Message::human("Hi")], 4096).unwrap();
+
+        assert_eq!(body.max_tokens, 100);
+        assert_eq!(body.temperature, Some(1.0));
+        // Claude models reject requests setting both
+        assert_eq!(body.top_p, None);
+        assert!(body.system.is_none());
+
+        let config = ModelConfig::new("claude-3-5-haiku-latest");
+        let body =
+            AnthropicRequestBody::from_model_config(&config, &[Message::human("Hi")], 4096).unwrap();
+        assert_eq!(body.max_tokens, 4096);
+
+        // The temperature is kept when `top_p` is set too
+        let config = ModelConfig::new("claude-3-5-haiku-latest")
+            .with_temperature(0.4)
+            .unwrap()
+            .with_top_p(0.5)
+            .unwrap();
+        let body =
+            AnthropicRequestBody::from_model_config(&config, &[Message::human("Hi")], 4096).unwrap();
+        assert_eq!(body.temperature, Some(0.4));
+        assert_eq!(body.top_p, None);
+    }
+
+    #[test]
+    fn test_request_body_presets() {
+        for (config, temperature) in [
+            (ModelConfig::conservative("claude-3-5-haiku-latest"), 0.3),
+            (ModelConfig::balanced("claude-3-5-haiku-latest"), 0.7),
+            // Anthropic accepts temperatures up to 1.0 only
+            (ModelConfig::creative("claude-3-5-haiku-latest"), 1.0),
+        ] {
+            let body =
+                AnthropicRequestBody::from_model_config(&config, &[Message::human("Hi")], 4096)
+                    .unwrap();
+            assert_eq!(body.temperature, Some(temperature));
+            assert_eq!(body.top_p, None);
+        }
+    }
+
+    #[test]
+    fn test_request_body_must_start_with_user_turn() {
+        let config = ModelConfig::new("claude-sonnet-4-0");
+        let messages = [
+            Message::system("Be nice."),
+            Message::assistant("Hello! How can I help?"),
+            Message::human("Hi"),
+        ];
+
+        assert!(matches!(
+            AnthropicRequestBody::from_model_config(&config, &messages, 4096),
+            Err(OrchestraError::InvalidRequest { .. })
+        ));
+    }
+
+    #[test]
+    fn test_request_body_images_and_documents() {
+        let config = ModelConfig::new("claude-sonnet-4-0");
+        let messages = [
+            Message::human("Here are two files."),
+            Message::human_with_parts(vec![
+                ContentPart::inline_data("image/png", b"png"),
+                ContentPart::inline_data("application/pdf", b"%PDF-"),
+                ContentPart::text("Compare them."),
+            ]),
+        ];
+
+        let body = AnthropicRequestBody::from_model_config(&config, &messages, 4096).unwrap();
+        assert_eq!(
+            serde_json::to_value(&body.messages).unwrap(),
+            serde_json::json!([{"role": "user", "content": [
+                {"type": "text", "text": "Here are two files."},
+                {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "cG5n"}},
+                {"type": "document", "source": {"type": "base64", "media_type": "application/pdf", "data": "JVBERi0="}},
+                {"type": "text", "text": "Compare them."}
+            ]}])
+        );
+
+        let messages = [Message::human_with_parts(vec![ContentPart::inline_data("video/mp4", b"")])];
+        assert!(matches!(
+            AnthropicRequestBody::from_model_config(&config, &messages, 4096),
+            Err(OrchestraError::InvalidRequest { .. })
+        ));
+    }
+
+    #[test]
+    fn test_error_message_parsing() {
+        let body = serde_json::json!({"type": "error", "error": {
+            "type": "invalid_request_error",
+            "message": "max_tokens: field required"
+        }});
+        assert_eq!(
+            AnthropicErrorResponse::parse_message(&body.to_string()).as_deref(),
+            Some("invalid_request_error: max_tokens: field required")
+        );
+        assert_eq!(AnthropicErrorResponse::parse_message("Overloaded"), None);
+    }
+
+    #[tokio::test]
+    async fn test_chat_retry_then_client_error() {
+        let server = TestServer::start(vec![
+            MockResponse::status(503, serde_json::json!({"type": "error", "error": {"type": "api_error", "message": "Unavailable"}})),
+            MockResponse::status(400, serde_json::json!({"type": "error", "error": {"type": "invalid_request_error", "message": "Bad request"}})),
+        ])
+        .await;
+        let mut config = AnthropicConfig::new().with_api_key("test-key").with_base_url(server.url());
+        config.base = config.base.with_max_retries(3);
+        let provider = AnthropicProvider::new(config);
+
+        let result = provider
+            .prompt(ModelConfig::new("claude-sonnet-4-0"), "Hello".to_string())
+            .await;
+
+        match result {
+            Err(error @ OrchestraError::InvalidRequest { .. }) => {
+                assert_eq!(error.status_code(), Some(400));
+                assert!(!error.is_retryable());
+            }
+            other => panic!("Expected invalid request error, got {:?}", other),
+        }
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_finish_reason_mapping() {
+        for (reason, expected) in [
+            ("end_turn", FinishReason::Stop),
+            ("stop_sequence", FinishReason::Stop),
+            ("max_tokens", FinishReason::MaxTokens),
+            ("tool_use", FinishReason::ToolCalls),
+            ("refusal", FinishReason::Safety),
+            ("pause_turn", FinishReason::Other("pause_turn".to_string())),
+        ] {
+            let response: AnthropicChatResponse =
+                serde_json::from_value(anthropic_message("", reason)).unwrap();
+            assert_eq!(response.finish_reason(), Some(expected));
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod r#impl;
+mod types;
+
+pub use r#impl::*;
+pub use types::*;
@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{OrchestraError, Result},
+    messages::{ContentPart, Message, MessageContent},
+    model::ModelConfig,
+    providers::types::{FinishReason, Usage, unsupported_content},
+};
+
+pub const PREDEFINED_MODELS: &[&str] = &[
+    "claude-opus-4-1",
+    "claude-opus-4-0",
+    "claude-sonnet-4-0",
+    "claude-3-7-sonnet-latest",
+    "claude-3-5-haiku-latest",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicRequestBody {
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    pub messages: Vec<AnthropicMessage>,
+    pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<Vec<String>>,
+}
+
+impl AnthropicRequestBody {
+    /// Build a request body from the model configuration and the messages to send.
+    ///
+    /// The Messages API takes the system prompt as a top-level field and expects user
+    /// and assistant turns to alternate, starting with a user turn, so system messages
+    /// found in `messages` are appended to `ModelConfig::system_instruction` and
+    /// consecutive turns with the same role are merged into one. A conversation
+    /// starting with an assistant turn is rejected.
+    ///
+    /// Claude models accept either `temperature` or `top_p`, not both, so only the
+    /// temperature is sent and `ModelConfig::top_p` is ignored. Anthropic accepts
+    /// temperatures up to 1.0, so higher ones, such as the one of
+    /// [`ModelConfig::creative`], are clamped to 1.0.
+    ///
+    /// Inline images and PDFs on user turns are sent as `image` and `document` blocks;
+    /// other files are rejected.
+    ///
+    /// Tool calling is not supported yet: `ModelConfig::tools` is not sent, and tool
+    /// results in the history are sent as plain text on the user's turn rather than as
+    /// `tool_result` blocks.
+    pub fn from_model_config(
+        config: &ModelConfig,
+        messages: &[Message],
+        default_max_tokens: u32,
+    ) -> Result<Self> {
+        let mut system_parts: Vec<String> = config.system_instruction.iter().cloned().collect();
+        let mut turns: Vec<AnthropicMessage> = Vec::new();
+
+        for message in messages {
+            let role = match message {
+                Message::System(s) => {
+                    system_parts.push(s.content.clone());
+                    continue;
+                }
+                // Tool results are passed back as plain text on the user's turn
+                Message::Human(_) | Message::Tool(_) => "user",
+                Message::Assistant(_) => "assistant",
+            };
+
+            let content = AnthropicContent::from_message(message)?;
+            if content.is_empty() {
+                continue;
+            }
+
+            match turns.last_mut() {
+                Some(last) if last.role == role => last.content.append(content),
+                _ => turns.push(AnthropicMessage {
+                    role: role.to_string(),
+                    content,
+                }),
+            }
+        }
+
+        if turns.first().is_some_and(|turn| turn.role != "user") {
+            return Err(OrchestraError::invalid_request(
+                "Anthropic conversations must start with a user message",
+            ));
+        }
+
+        Ok(Self {
+            model: config.name.clone(),
+            system: if system_parts.is_empty() {
+                None
+            } else {
+                Some(system_parts.join("\n\n"))
+            },
+            messages: turns,
+            max_tokens: config.max_tokens.unwrap_or(default_max_tokens),
+            temperature: Some(config.temperature.min(1.0)),
+            // Claude models reject requests setting both
+            top_p: None,
+            top_k: config.top_k,
+            stop_sequences: if config.stop_sequences.is_empty() {
+                None
+            } else {
+                Some(config.stop_sequences.clone())
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicMessage {
+    pub role: String,
+    pub content: AnthropicContent,
+}
+
+/// Turn content: a plain string, or content blocks when it carries images or documents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AnthropicContent {
+    Text(String),
+    Blocks(Vec<AnthropicContentBlockParam>),
+}
+
+/// A content block sent on a turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicContentBlockParam {
+    Text { text: String },
+    Image { source: AnthropicSource },
+    Document { source: AnthropicSource },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicSource {
+    /// Always `base64`
+    #[serde(rename = "type")]
+    pub source_type: String,
+    pub media_type: String,
+    pub data: String,
+}
+
+impl AnthropicContent {
+    /// Map the content of a message; only user turns may carry images and PDFs
+    fn from_message(message: &Message) -> Result<Self> {
+        let parts = match message {
+            Message::Human(h) => match &h.content {
+                MessageContent::Parts(parts) if h.content.media_parts().next().is_some() => parts,
+                _ => return Ok(Self::Text(message.content_text())),
+            },
+            Message::Assistant(a) => match a.content.media_parts().next() {
+                Some(part) => return Err(unsupported_content("anthropic", part)),
+                None => return Ok(Self::Text(message.content_text())),
+            },
+            Message::System(_) | Message::Tool(_) => {
+                return Ok(Self::Text(message.content_text()));
+            }
+        };
+
+        parts
+            .iter()
+            .map(|part| match part {
+                ContentPart::Text(text) => Ok(AnthropicContentBlockParam::Text { text: text.clone() }),
+                ContentPart::InlineData { mime_type, data } => {
+                    let source = AnthropicSource {
+                        source_type: "base64".to_string(),
+                        media_type: mime_type.clone(),
+                        data: data.clone(),
+                    };
+                    match mime_type.as_str() {
+                        "image/jpeg" | "image/png" | "image/gif" | "image/webp" => {
+                            Ok(AnthropicContentBlockParam::Image { source })
+                        }
+                        "application/pdf" => Ok(AnthropicContentBlockParam::Document { source }),
+                        _ => Err(unsupported_content("anthropic", part)),
+                    }
+                }
+                ContentPart::FileData { .. } => Err(unsupported_content("anthropic", part)),
+            })
+            .collect::<Result<_>>()
+            .map(Self::Blocks)
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Text(text) => text.is_empty(),
+            Self::Blocks(blocks) => blocks.is_empty(),
+        }
+    }
+
+    /// Append the content of a following message with the same role
+    fn append(&mut self, other: Self) {
+        match (&mut *self, other) {
+            (Self::Text(text), Self::Text(other)) => {
+                text.push_str("\n\n");
+                text.push_str(&other);
+            }
+            (Self::Text(text), Self::Blocks(blocks)) => {
+                let text = std::mem::take(text);
+                *self = Self::Blocks(
+                    std::iter::once(AnthropicContentBlockParam::Text { text })
+                        .chain(blocks)
+                        .collect(),
+                );
+            }
+            (Self::Blocks(blocks), Self::Text(text)) => {
+                blocks.push(AnthropicContentBlockParam::Text { text });
+            }
+            (Self::Blocks(blocks), Self::Blocks(other)) => blocks.extend(other),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnthropicChatResponse {
+    pub id: Option<String>,
+    pub model: Option<String>,
+    #[serde(default)]
+    pub content: Vec<AnthropicContentBlock>,
+    pub stop_reason: Option<String>,
+    pub usage: Option<AnthropicUsage>,
+}
+
+impl AnthropicChatResponse {
+    /// Get the normalized finish reason, if the response has one
+    pub fn finish_reason(&self) -> Option<FinishReason> {
+        self.stop_reason.as_deref().map(|reason| match reason {
+            "end_turn" | "stop_sequence" => FinishReason::Stop,
+            "max_tokens" => FinishReason::MaxTokens,
+            "tool_use" => FinishReason::ToolCalls,
+            "refusal" => FinishReason::Safety,
+            other => FinishReason::Other(other.to_string()),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnthropicContentBlock {
+    #[serde(rename = "type")]
+    pub block_type: String,
+    pub text: Option<String>,
+    /// Reasoning summary of a `thinking` block
+    pub thinking: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnthropicUsage {
+    #[serde(default)]
+    pub input_tokens: u32,
+    #[serde(default)]
+    pub output_tokens: u32,
+}
+
+impl From<&AnthropicUsage> for Usage {
+    fn from(usage: &AnthropicUsage) -> Self {
+        Usage {
+            prompt_tokens: usage.input_tokens,
+            completion_tokens: usage.output_tokens,
+            total_tokens: usage.input_tokens + usage.output_tokens,
+            thoughts_tokens: None,
+        }
+    }
+}
+
+/// Error body returned by the Anthropic API, e.g. `{"type": "error", "error": {...}}`
+#[derive(Debug, Deserialize)]
+pub struct AnthropicErrorResponse {
+    pub error: AnthropicError,
+}
+
+impl AnthropicErrorResponse {
+    /// The type and message of an error body, if it has the expected shape
+    pub fn parse_message(body: &str) -> Option<String> {
+        serde_json::from_str::<Self>(body)
+            .ok()
+            .map(|e| format!("{}: {}", e.error.error_type, e.error.message))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnthropicError {
+    #[serde(rename = "type")]
+    pub error_type: String,
+    pub message: String,
+}
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
+
+use crate::{
+    error::{OrchestraError, Result},
+    providers::gemini::GeminiFiles,
+};
 
 /// Configuration for a provider
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,8 +15,22 @@ pub struct ProviderConfig {
     pub base_url: Option<String>,
     /// Additional headers to include in requests
     pub headers: HashMap<String, String>,
-    /// Request timeout in seconds
+    /// Read timeout in seconds: the longest wait for the server to send more data,
+    /// rather than a limit on the whole request, so long streamed replies are not cut
+    /// off. Defaults to [`DEFAULT_TIMEOUT_SECONDS`](Self::DEFAULT_TIMEOUT_SECONDS);
+    /// `None` waits indefinitely.
     pub timeout_seconds: Option<u64>,
+    /// Connection timeout in seconds, covering DNS, TCP and TLS setup
+    pub connect_timeout_seconds: Option<u64>,
+    /// Maximum number of idle connections kept open per host
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Speak HTTP/2 without negotiating it first, for servers known to support it
+    #[serde(default)]
+    pub http2_prior_knowledge: bool,
+    /// `User-Agent` header; defaults to `orchestra-rs/<version>`
+    pub user_agent: Option<String>,
+    /// Proxy URL all requests are routed through, e.g. `http://proxy.internal:3128`
+    pub proxy: Option<String>,
     /// Maximum number of retries for failed requests
     pub max_retries: Option<u32>,
     /// Custom configuration specific to the provider
@@ -24,7 +43,12 @@ impl Default for ProviderConfig {
             api_key: None,
             base_url: None,
             headers: HashMap::new(),
-            timeout_seconds: Some(30),
+            timeout_seconds: Some(Self::DEFAULT_TIMEOUT_SECONDS),
+            connect_timeout_seconds: None,
+            pool_max_idle_per_host: None,
+            http2_prior_knowledge: false,
+            user_agent: None,
+            proxy: None,
             max_retries: Some(3),
             custom: HashMap::new(),
         }
@@ -32,6 +56,11 @@ impl Default for ProviderConfig {
 }
 
 impl ProviderConfig {
+    /// Default read timeout; long enough for a non-streaming generation, which sends
+    /// nothing until the whole reply is ready. Earlier versions defaulted to a 30s
+    /// timeout for the whole request.
+    pub const DEFAULT_TIMEOUT_SECONDS: u64 = 300;
+
     /// Create a new provider configuration
     pub fn new() -> Self {
         Self::default()
@@ -55,12 +84,48 @@ impl ProviderConfig {
         self
     }
 
-    /// Set the timeout
+    /// Set the read timeout: the longest wait for the server to send more data
     pub fn with_timeout(mut self, timeout_seconds: u64) -> Self {
         self.timeout_seconds = Some(timeout_seconds);
         self
     }
 
+    /// Wait indefinitely for the server to send more data
+    pub fn without_timeout(mut self) -> Self {
+        self.timeout_seconds = None;
+        self
+    }
+
+    /// Set the connection timeout
+    pub fn with_connect_timeout(mut self, connect_timeout_seconds: u64) -> Self {
+        self.connect_timeout_seconds = Some(connect_timeout_seconds);
+        self
+    }
+
+    /// Set the maximum number of idle connections kept open per host
+    pub fn with_pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max_idle);
+        self
+    }
+
+    /// Speak HTTP/2 without negotiating it first
+    pub fn with_http2_prior_knowledge(mut self, http2_prior_knowledge: bool) -> Self {
+        self.http2_prior_knowledge = http2_prior_knowledge;
+        self
+    }
+
+    /// Set the `User-Agent` header
+    pub fn with_user_agent<S: Into<String>>(mut self, user_agent: S) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Route all requests through a proxy
+    pub fn with_proxy<S: Into<String>>(mut self, proxy: S) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
     /// Set the maximum number of retries
     pub fn with_max_retries(mut self, max_retries: u32) -> Self {
         self.max_retries = Some(max_retries);
@@ -83,9 +148,17 @@ impl ProviderConfig {
         self.base_url.clone().unwrap_or_else(|| default.to_string())
     }
 
-    /// Get the timeout in seconds
+    /// Get the read timeout in seconds, or `u64::MAX` when there is none.
+    ///
+    /// The timeout limits each wait for more data, not the whole request; see
+    /// [`get_read_timeout`](Self::get_read_timeout) to tell a missing timeout apart.
     pub fn get_timeout(&self) -> u64 {
-        self.timeout_seconds.unwrap_or(30)
+        self.timeout_seconds.unwrap_or(u64::MAX)
+    }
+
+    /// Get the read timeout in seconds, if there is one
+    pub fn get_read_timeout(&self) -> Option<u64> {
+        self.timeout_seconds
     }
 
     /// Get the maximum number of retries
@@ -93,21 +166,76 @@ impl ProviderConfig {
         self.max_retries.unwrap_or(3)
     }
 
+    /// Build an HTTP client with this configuration's timeouts, pooling, protocol,
+    /// user agent and proxy settings.
+    ///
+    /// Clients are meant to be built once and reused, so that connections (and TLS
+    /// sessions) are shared between requests.
+    pub fn build_http_client(&self) -> Result<reqwest::Client> {
+        let user_agent = self
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| concat!("orchestra-rs/", env!("CARGO_PKG_VERSION")).to_string());
+
+        let mut builder = reqwest::Client::builder().user_agent(user_agent);
+
+        if let Some(timeout) = self.get_read_timeout() {
+            builder = builder.read_timeout(Duration::from_secs(timeout));
+        }
+        if let Some(connect_timeout) = self.connect_timeout_seconds {
+            builder = builder.connect_timeout(Duration::from_secs(connect_timeout));
+        }
+        if let Some(max_idle) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(proxy) = &self.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| OrchestraError::config(format!("Invalid proxy {}: {}", proxy, e)))?;
+            builder = builder.proxy(proxy);
+        }
+
+        builder
+            .build()
+            .map_err(|e| OrchestraError::config(format!("Could not build HTTP client: {}", e)))
+    }
+
     /// Get a custom configuration value
     pub fn get_custom(&self, key: &str) -> Option<&serde_json::Value> {
         self.custom.get(key)
     }
 }
 
+/// How [`Message::System`](crate::messages::Message::System) entries in the chat history
+/// are sent to providers without a system role, such as Gemini
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemMessagePolicy {
+    /// Add them to the system instruction, after the model configuration's instruction
+    #[default]
+    Merge,
+    /// Use them instead of the model configuration's instruction, if there are any
+    Replace,
+    /// Send them as user turns, keeping their position in the conversation
+    AsUser,
+    /// Leave them out of the request
+    Ignore,
+}
+
 /// Configuration specific to Gemini provider
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeminiConfig {
     /// Base provider configuration
     pub base: ProviderConfig,
-    /// Whether to use the beta API
+    /// Whether to use the beta API; when `false` the stable `v1` API is used
     pub use_beta: bool,
-    /// API version to use
+    /// API version to use with the beta API
     pub api_version: String,
+    /// How system messages in the chat history are sent
+    #[serde(default)]
+    pub system_message_policy: SystemMessagePolicy,
 }
 
 impl Default for GeminiConfig {
@@ -116,11 +244,15 @@ impl Default for GeminiConfig {
             base: ProviderConfig::default(),
             use_beta: true,
             api_version: "v1beta".to_string(),
+            system_message_policy: SystemMessagePolicy::default(),
         }
     }
 }
 
 impl GeminiConfig {
+    /// Default API root; the API version is appended to it
+    pub const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+
     /// Create a new Gemini configuration
     pub fn new() -> Self {
         Self::default()
@@ -132,6 +264,20 @@ impl GeminiConfig {
         self
     }
 
+    /// Set the API root, e.g. a proxy in front of the Gemini API. The API version is
+    /// appended to it, unless it already ends in one like `.../v1beta`, in which case
+    /// it is used as is.
+    pub fn with_base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base = self.base.with_base_url(base_url);
+        self
+    }
+
+    /// Set a header sent with every request
+    pub fn with_header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.base = self.base.with_header(key, value);
+        self
+    }
+
     /// Set whether to use the beta API
     pub fn with_beta(mut self, use_beta: bool) -> Self {
         self.use_beta = use_beta;
@@ -144,12 +290,44 @@ impl GeminiConfig {
         self
     }
 
-    /// Get the base URL for Gemini API
+    /// Set how system messages in the chat history are sent
+    pub fn with_system_message_policy(mut self, policy: SystemMessagePolicy) -> Self {
+        self.system_message_policy = policy;
+        self
+    }
+
+    /// Get the API version requests are sent to
+    pub fn get_api_version(&self) -> &str {
+        if self.use_beta { &self.api_version } else { "v1" }
+    }
+
+    /// Get the base URL for Gemini API, including the API version
     pub fn get_base_url(&self) -> String {
-        if self.use_beta {
-            format!("https://generativelanguage.googleapis.com/{}", self.api_version)
+        let base_url = self.base.get_base_url(Self::DEFAULT_BASE_URL);
+        let base_url = base_url.trim_end_matches('/');
+
+        // A version segment such as `v1` or `v1beta` means the URL is already complete
+        let has_version = base_url.rsplit_once('/').is_some_and(|(_, segment)| {
+            segment
+                .strip_prefix('v')
+                .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+                && segment.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+
+        if has_version {
+            base_url.to_string()
         } else {
-            "https://generativelanguage.googleapis.com/v1".to_string()
+            format!("{}/{}", base_url, self.get_api_version())
+        }
+    }
+
+    /// Get the base URL for the Files API, which is only served by `v1beta` whatever
+    /// version the other requests use
+    pub fn get_files_base_url(&self) -> String {
+        let base_url = self.get_base_url();
+        match base_url.rsplit_once('/') {
+            Some((root, _version)) => format!("{}/{}", root, GeminiFiles::API_VERSION),
+            None => base_url,
         }
     }
 
@@ -158,3 +336,184 @@ impl GeminiConfig {
         self.base.get_api_key("GEMINI_API_KEY")
     }
 }
+
+/// Configuration specific to OpenAI provider
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenAIConfig {
+    /// Base provider configuration
+    pub base: ProviderConfig,
+    /// Organization ID sent in the `OpenAI-Organization` header
+    pub organization: Option<String>,
+}
+
+impl OpenAIConfig {
+    /// Default base URL for the OpenAI API
+    pub const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+    /// Create a new OpenAI configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the API key
+    pub fn with_api_key<S: Into<String>>(mut self, api_key: S) -> Self {
+        self.base = self.base.with_api_key(api_key);
+        self
+    }
+
+    /// Set the base URL
+    pub fn with_base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base = self.base.with_base_url(base_url);
+        self
+    }
+
+    /// Set a header sent with every request
+    pub fn with_header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.base = self.base.with_header(key, value);
+        self
+    }
+
+    /// Set the organization ID
+    pub fn with_organization<S: Into<String>>(mut self, organization: S) -> Self {
+        self.organization = Some(organization.into());
+        self
+    }
+
+    /// Get the base URL for OpenAI API
+    pub fn get_base_url(&self) -> String {
+        self.base.get_base_url(Self::DEFAULT_BASE_URL)
+    }
+
+    /// Get the API key from configuration or environment
+    pub fn get_api_key(&self) -> Option<String> {
+        self.base.get_api_key("OPENAI_API_KEY")
+    }
+}
+
+/// Configuration specific to Anthropic provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicConfig {
+    /// Base provider configuration
+    pub base: ProviderConfig,
+    /// Value of the `anthropic-version` header
+    pub api_version: String,
+    /// `max_tokens` sent when the model configuration does not set one, since the
+    /// Messages API requires it on every request
+    pub default_max_tokens: u32,
+}
+
+impl Default for AnthropicConfig {
+    fn default() -> Self {
+        Self {
+            base: ProviderConfig::default(),
+            api_version: "2023-06-01".to_string(),
+            default_max_tokens: 4096,
+        }
+    }
+}
+
+impl AnthropicConfig {
+    /// Default base URL for the Anthropic API
+    pub const DEFAULT_BASE_URL: &str = "https://api.anthropic.com/v1";
+
+    /// Create a new Anthropic configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the API key
+    pub fn with_api_key<S: Into<String>>(mut self, api_key: S) -> Self {
+        self.base = self.base.with_api_key(api_key);
+        self
+    }
+
+    /// Set the base URL
+    pub fn with_base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base = self.base.with_base_url(base_url);
+        self
+    }
+
+    /// Set a header sent with every request
+    pub fn with_header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.base = self.base.with_header(key, value);
+        self
+    }
+
+    /// Set the API version
+    pub fn with_api_version<S: Into<String>>(mut self, api_version: S) -> Self {
+        self.api_version = api_version.into();
+        self
+    }
+
+    /// Set the default maximum number of tokens to generate
+    pub fn with_default_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.default_max_tokens = max_tokens;
+        self
+    }
+
+    /// Get the base URL for Anthropic API
+    pub fn get_base_url(&self) -> String {
+        self.base.get_base_url(Self::DEFAULT_BASE_URL)
+    }
+
+    /// Get the API key from configuration or environment
+    pub fn get_api_key(&self) -> Option<String> {
+        self.base.get_api_key("ANTHROPIC_API_KEY")
+    }
+}
+
+/// Configuration specific to Ollama provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaConfig {
+    /// Base provider configuration
+    pub base: ProviderConfig,
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        // Loading a model into memory and generating on local hardware can take
+        // minutes, so requests wait as long as the server needs
+        Self {
+            base: ProviderConfig::default().without_timeout(),
+        }
+    }
+}
+
+impl OllamaConfig {
+    /// Default base URL of a locally running Ollama server
+    pub const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+    /// Create a new Ollama configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the base URL
+    pub fn with_base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base = self.base.with_base_url(base_url);
+        self
+    }
+
+    /// Set the request timeout; there is none by default
+    pub fn with_timeout(mut self, timeout_seconds: u64) -> Self {
+        self.base = self.base.with_timeout(timeout_seconds);
+        self
+    }
+
+    /// Get the base URL for Ollama API, falling back to the `OLLAMA_HOST`
+    /// environment variable and then to the local default
+    pub fn get_base_url(&self) -> String {
+        self.base.base_url.clone().unwrap_or_else(|| {
+            std::env::var("OLLAMA_HOST")
+                .ok()
+                .map(|host| {
+                    if host.starts_with("http://") || host.starts_with("https://") {
+                        host
+                    } else {
+                        format!("http://{}", host)
+                    }
+                })
+                .unwrap_or_else(|| Self::DEFAULT_BASE_URL.to_string())
+        })
+    }
+}
@@ -0,0 +1,434 @@
+//! Client for the Gemini Files API.
+//!
+//! Files uploaded through [`GeminiFiles`] are stored by Google for 48 hours and can
+//! be referenced from any number of prompts without re-sending their contents:
+//!
+//! ```rust,ignore
+//! let provider = GeminiProvider::with_default_config();
+//! let file = provider.files()?.upload_file("lecture.mp4", None).await?;
+//!
+//! let message = Message::human_with_parts(vec![
+//!     ContentPart::text("Summarize this lecture"),
+//!     file.to_content_part(),
+//! ]);
+//! ```
+
+use std::path::Path;
+
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    error::{OrchestraError, Result},
+    messages::{ContentPart, media::detect_mime_type},
+    providers::{gemini::GeminiErrorResponse, http::error_from_response, retry::RetryPolicy},
+};
+
+/// Processing state of an uploaded file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GeminiFileState {
+    #[default]
+    StateUnspecified,
+    /// The file is still being processed and cannot be used yet
+    Processing,
+    /// The file is ready to be used in prompts
+    Active,
+    /// Processing failed
+    Failed,
+}
+
+/// Metadata of a file stored by the Files API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeminiFile {
+    /// Resource name, e.g. `files/abc-123`
+    pub name: String,
+    #[serde(rename = "displayName", default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(rename = "mimeType", default)]
+    pub mime_type: String,
+    /// Size in bytes; the API encodes it as a string
+    #[serde(rename = "sizeBytes", default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<String>,
+    #[serde(rename = "createTime", default, skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<String>,
+    #[serde(rename = "updateTime", default, skip_serializing_if = "Option::is_none")]
+    pub update_time: Option<String>,
+    #[serde(rename = "expirationTime", default, skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<String>,
+    #[serde(rename = "sha256Hash", default, skip_serializing_if = "Option::is_none")]
+    pub sha256_hash: Option<String>,
+    /// URI used to reference the file from prompts
+    #[serde(default)]
+    pub uri: String,
+    #[serde(default)]
+    pub state: GeminiFileState,
+}
+
+impl GeminiFile {
+    /// The file size in bytes, if reported
+    pub fn size(&self) -> Option<u64> {
+        self.size_bytes.as_deref().and_then(|s| s.parse().ok())
+    }
+
+    /// Whether the file is ready to be used in prompts
+    pub fn is_active(&self) -> bool {
+        self.state == GeminiFileState::Active
+    }
+
+    /// Reference the file from message content as a `fileData` part
+    pub fn to_content_part(&self) -> ContentPart {
+        ContentPart::file_uri(&self.mime_type, &self.uri)
+    }
+}
+
+impl From<&GeminiFile> for ContentPart {
+    fn from(file: &GeminiFile) -> Self {
+        file.to_content_part()
+    }
+}
+
+impl From<GeminiFile> for ContentPart {
+    fn from(file: GeminiFile) -> Self {
+        file.to_content_part()
+    }
+}
+
+/// One page of results from [`GeminiFiles::list`]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GeminiFileList {
+    #[serde(default)]
+    pub files: Vec<GeminiFile>,
+    /// Token to pass to the next `list` call; `None` on the last page
+    #[serde(rename = "nextPageToken", default)]
+    pub next_page_token: Option<String>,
+}
+
+/// Response wrapper used by the upload endpoint
+#[derive(Debug, Deserialize)]
+struct GeminiFileEnvelope {
+    file: GeminiFile,
+}
+
+/// Client for uploading and managing files through the Gemini Files API.
+///
+/// Obtain one from [`GeminiProvider::files`](super::GeminiProvider::files).
+#[derive(Debug, Clone)]
+pub struct GeminiFiles {
+    client: reqwest::Client,
+    api_key: String,
+    /// Extra headers sent with every request
+    headers: HeaderMap,
+    /// API root including the version, e.g. `https://generativelanguage.googleapis.com/v1beta`
+    base_url: String,
+    retry_policy: RetryPolicy,
+}
+
+impl GeminiFiles {
+    /// The only API version serving the Files API
+    pub const API_VERSION: &str = "v1beta";
+
+    /// Create a client for the API rooted at `base_url`
+    pub fn new<K: Into<String>, U: Into<String>>(api_key: K, base_url: U) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: api_key.into(),
+            headers: HeaderMap::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Send requests through an existing client, sharing its connections and settings
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Send extra headers with every request, e.g. for a proxy in front of the API
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Set how transient failures are retried
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Upload a file from disk, detecting its MIME type from its contents or extension.
+    ///
+    /// Returns [`OrchestraError::InvalidRequest`] if the MIME type cannot be detected.
+    pub async fn upload_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        display_name: Option<&str>,
+    ) -> Result<GeminiFile> {
+        let path = path.as_ref();
+        let bytes = tokio::fs::read(path).await?;
+        let mime_type = detect_mime_type(path, &bytes).ok_or_else(|| {
+            OrchestraError::invalid_request(format!(
+                "Could not detect the MIME type of {}",
+                path.display()
+            ))
+        })?;
+        let display_name = display_name
+            .map(str::to_string)
+            .or_else(|| path.file_name().map(|n| n.to_string_lossy().into_owned()));
+
+        self.upload(bytes, mime_type, display_name.as_deref()).await
+    }
+
+    /// Upload raw bytes using the resumable upload protocol.
+    ///
+    /// The first request announces the upload and returns a session URL; the
+    /// contents are then sent to that URL in a single finalizing request.
+    pub async fn upload<B: Into<Vec<u8>>>(
+        &self,
+        bytes: B,
+        mime_type: &str,
+        display_name: Option<&str>,
+    ) -> Result<GeminiFile> {
+        let bytes = bytes.into();
+
+        let mut headers = self.build_headers()?;
+        headers.insert("X-Goog-Upload-Protocol", "resumable".parse()?);
+        headers.insert("X-Goog-Upload-Command", "start".parse()?);
+        headers.insert(
+            "X-Goog-Upload-Header-Content-Length",
+            bytes.len().to_string().parse()?,
+        );
+        headers.insert("X-Goog-Upload-Header-Content-Type", mime_type.parse()?);
+
+        let metadata = match display_name {
+            Some(name) => json!({ "file": { "display_name": name } }),
+            None => json!({ "file": {} }),
+        };
+
+        let request = self
+            .client
+            .post(self.upload_url())
+            .headers(headers)
+            .json(&metadata);
+        let resp = self.retry_policy.send(request, Self::http_error).await?;
+
+        let session_url = resp
+            .headers()
+            .get("x-goog-upload-url")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                OrchestraError::invalid_response("Upload response is missing the upload URL")
+            })?;
+
+        let mut headers = self.build_headers()?;
+        headers.insert("X-Goog-Upload-Offset", "0".parse()?);
+        headers.insert("X-Goog-Upload-Command", "upload, finalize".parse()?);
+
+        let request = self.client.post(session_url).headers(headers).body(bytes);
+        let resp = self.retry_policy.send(request, Self::http_error).await?;
+        let envelope: GeminiFileEnvelope = resp.json().await?;
+
+        Ok(envelope.file)
+    }
+
+    /// Fetch the metadata of a file, e.g. to poll its processing state.
+    ///
+    /// Accepts either the resource name (`files/abc-123`) or the bare id.
+    pub async fn get(&self, name: &str) -> Result<GeminiFile> {
+        let request = self.client.get(self.file_url(name)).headers(self.build_headers()?);
+        let resp = self.retry_policy.send(request, Self::http_error).await?;
+
+        Ok(resp.json().await?)
+    }
+
+    /// List uploaded files, one page at a time
+    pub async fn list(
+        &self,
+        page_size: Option<u32>,
+        page_token: Option<&str>,
+    ) -> Result<GeminiFileList> {
+        let mut query = Vec::new();
+        if let Some(page_size) = page_size {
+            query.push(("pageSize", page_size.to_string()));
+        }
+        if let Some(page_token) = page_token {
+            query.push(("pageToken", page_token.to_string()));
+        }
+
+        let request = self
+            .client
+            .get(format!("{}/files", self.base_url))
+            .headers(self.build_headers()?)
+            .query(&query);
+        let resp = self.retry_policy.send(request, Self::http_error).await?;
+
+        Ok(resp.json().await?)
+    }
+
+    /// Delete a file before it expires
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        let request = self.client.delete(self.file_url(name)).headers(self.build_headers()?);
+        self.retry_policy.send(request, Self::http_error).await?;
+
+        Ok(())
+    }
+
+    fn build_headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-goog-api-key", self.api_key.parse()?);
+        headers.extend(self.headers.clone());
+        Ok(headers)
+    }
+
+    /// Uploads go to `/upload/{version}/files` on the same host
+    fn upload_url(&self) -> String {
+        match self.base_url.rsplit_once('/') {
+            Some((origin, version)) => format!("{}/upload/{}/files", origin, version),
+            None => format!("{}/upload/files", self.base_url),
+        }
+    }
+
+    fn file_url(&self, name: &str) -> String {
+        if name.starts_with("files/") {
+            format!("{}/{}", self.base_url, name)
+        } else {
+            format!("{}/files/{}", self.base_url, name)
+        }
+    }
+
+    /// Turn an unsuccessful response into an error
+    async fn http_error(resp: reqwest::Response) -> OrchestraError {
+        error_from_response("gemini", resp, GeminiErrorResponse::parse_message).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::test_server::{MockResponse, TestServer};
+
+    fn file_json() -> serde_json::Value {
+        json!({
+            "name": "files/abc-123",
+            "displayName": "notes.txt",
+            "mimeType": "text/plain",
+            "sizeBytes": "11",
+            "createTime": "2025-01-01T00:00:00Z",
+            "expirationTime": "2025-01-03T00:00:00Z",
+            "sha256Hash": "ZmFrZQ==",
+            "uri": "https://generativelanguage.googleapis.com/v1beta/files/abc-123",
+            "state": "ACTIVE"
+        })
+    }
+
+    #[tokio::test]
+    async fn test_upload_resumable_handshake() {
+        let server = TestServer::start(vec![]).await;
+        server.enqueue(
+            MockResponse::json(json!({}))
+                .with_header("x-goog-upload-url", format!("{}/upload-session/1", server.url())),
+        );
+        server.enqueue(MockResponse::json(json!({ "file": file_json() })));
+
+        let files = GeminiFiles::new("test-key", format!("{}/v1beta", server.url()));
+        let file = files
+            .upload(b"hello world".to_vec(), "text/plain", Some("notes.txt"))
+            .await
+            .unwrap();
+
+        assert_eq!(file.name, "files/abc-123");
+        assert_eq!(file.size(), Some(11));
+        assert!(file.is_active());
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+
+        let start = &requests[0];
+        assert_eq!(start.method, "POST");
+        assert_eq!(start.path, "/upload/v1beta/files");
+        assert_eq!(start.header("x-goog-api-key"), Some("test-key"));
+        assert_eq!(start.header("x-goog-upload-protocol"), Some("resumable"));
+        assert_eq!(start.header("x-goog-upload-command"), Some("start"));
+        assert_eq!(start.header("x-goog-upload-header-content-length"), Some("11"));
+        assert_eq!(start.header("x-goog-upload-header-content-type"), Some("text/plain"));
+        assert_eq!(start.json(), json!({ "file": { "display_name": "notes.txt" } }));
+
+        let finalize = &requests[1];
+        assert_eq!(finalize.method, "POST");
+        assert_eq!(finalize.path, "/upload-session/1");
+        assert_eq!(finalize.header("x-goog-upload-offset"), Some("0"));
+        assert_eq!(finalize.header("x-goog-upload-command"), Some("upload, finalize"));
+        assert_eq!(finalize.body, b"hello world");
+    }
+
+    #[tokio::test]
+    async fn test_upload_without_session_url() {
+        let server = TestServer::start(vec![MockResponse::json(json!({}))]).await;
+
+        let files = GeminiFiles::new("test-key", format!("{}/v1beta", server.url()));
+        let result = files.upload(b"data".to_vec(), "text/plain", None).await;
+
+        assert!(matches!(result, Err(OrchestraError::InvalidResponse { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_upload_file_unknown_mime_type() {
+        let server = TestServer::start(vec![]).await;
+        let path = std::env::temp_dir().join(format!("orchestra-upload-{}.unknown", std::process::id()));
+        std::fs::write(&path, b"no known signature").unwrap();
+
+        let files = GeminiFiles::new("test-key", format!("{}/v1beta", server.url()));
+        let result = files.upload_file(&path, None).await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(OrchestraError::InvalidRequest { .. })));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_list_delete() {
+        let server = TestServer::start(vec![
+            MockResponse::json(file_json()),
+            MockResponse::json(json!({ "files": [file_json()], "nextPageToken": "page-2" })),
+            MockResponse::json(json!({})),
+            MockResponse::status(404, json!({ "error": { "code": 404 } })),
+        ])
+        .await;
+
+        let files = GeminiFiles::new("test-key", format!("{}/v1beta", server.url()));
+
+        let file = files.get("abc-123").await.unwrap();
+        assert_eq!(file.display_name.as_deref(), Some("notes.txt"));
+
+        let page = files.list(Some(10), Some("page-1")).await.unwrap();
+        assert_eq!(page.files, vec![file.clone()]);
+        assert_eq!(page.next_page_token.as_deref(), Some("page-2"));
+
+        files.delete("files/abc-123").await.unwrap();
+        assert!(files.get("files/missing").await.is_err());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/v1beta/files/abc-123");
+        assert_eq!(requests[1].path, "/v1beta/files?pageSize=10&pageToken=page-1");
+        assert_eq!(requests[2].method, "DELETE");
+        assert_eq!(requests[2].path, "/v1beta/files/abc-123");
+    }
+
+    #[test]
+    fn test_file_as_content_part() {
+        let file: GeminiFile = serde_json::from_value(file_json()).unwrap();
+
+        assert_eq!(
+            ContentPart::from(&file),
+            ContentPart::FileData {
+                mime_type: "text/plain".to_string(),
+                file_uri: "https://generativelanguage.googleapis.com/v1beta/files/abc-123"
+                    .to_string(),
+            }
+        );
+    }
+}
@@ -1,22 +1,35 @@
+use std::collections::{HashMap, VecDeque};
+
 use crate::{
     error::{OrchestraError, Result},
     messages::Message,
+    model::ModelConfig,
     providers::{
-        Provider, config::GeminiConfig, gemini::types::GeminiChatResponse, types::ChatResponse,
+        Provider,
+        config::{GeminiConfig, SystemMessagePolicy},
+        gemini::types::{GeminiChatResponse, GeminiErrorResponse},
+        http::{HttpClient, model_error_from_response},
+        retry::RetryPolicy,
+        sse::{SseEvent, SseParser},
+        types::{Candidate, ChatResponse, ChatStream, ChatStreamEvent, FinishReason, Usage},
     },
 };
 
 use async_trait::async_trait;
-use reqwest::header::HeaderMap;
+use futures::{Stream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName};
 
+use super::files::GeminiFiles;
 use super::types::{
-    GeminiContent, GeminiGenerationConfig, GeminiRequestBody, GeminiRequestPart, PREDEFINED_MODELS,
-    SystemInstruction,
+    GeminiContent, GeminiGenerationConfig, GeminiRequestBody, GeminiRequestPart, GeminiTool,
+    PREDEFINED_MODELS, SystemInstruction,
 };
 
 #[derive(Debug)]
 pub struct GeminiProvider {
     config: GeminiConfig,
+    base_url: String,
+    http: HttpClient,
 }
 
 impl GeminiProvider {
@@ -24,10 +37,304 @@ impl GeminiProvider {
 
     /// Create a new GeminiProvider with default configuration
     pub fn with_default_config() -> Self {
-        Self {
-            config: GeminiConfig::default(),
+        Self::new(GeminiConfig::default())
+    }
+
+    /// A client for the Files API, authenticated with this provider's API key
+    pub fn files(&self) -> Result<GeminiFiles> {
+        let api_key = self.config.get_api_key().ok_or_else(|| {
+            OrchestraError::api_key("API key not found in configuration or environment")
+        })?;
+
+        Ok(GeminiFiles::new(api_key, self.config.get_files_base_url())
+            .with_client(self.http.get()?.clone())
+            .with_headers(self.custom_headers()?)
+            .with_retry_policy(self.retry_policy()))
+    }
+
+    /// Retry settings derived from the provider configuration
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::from_config(&self.config.base)
+    }
+
+    /// Turn an unsuccessful response to a request naming a model into an error
+    async fn http_error(resp: reqwest::Response) -> OrchestraError {
+        model_error_from_response("gemini", resp, GeminiErrorResponse::parse_message).await
+    }
+
+    /// Build the headers shared by all Gemini requests
+    fn build_headers(&self) -> Result<HeaderMap> {
+        let api_key = self.config.get_api_key().ok_or_else(|| {
+            OrchestraError::api_key("API key not found in configuration or environment")
+        })?;
+
+        let mut headers = HeaderMap::new();
+
+        headers.insert("x-goog-api-key", api_key.parse()?);
+        headers.insert("Content-Type", "application/json".parse()?);
+        headers.extend(self.custom_headers()?);
+
+        Ok(headers)
+    }
+
+    /// The custom headers from the provider configuration
+    fn custom_headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        for (key, value) in &self.config.base.headers {
+            let name = HeaderName::from_bytes(key.as_bytes())
+                .map_err(|e| OrchestraError::config(format!("Invalid header name {}: {}", key, e)))?;
+            headers.insert(name, value.parse()?);
+        }
+
+        Ok(headers)
+    }
+
+    /// Build the request body from the model configuration and conversation
+    fn build_request_body(
+        model_config: &ModelConfig,
+        system_message_policy: SystemMessagePolicy,
+        message: Message,
+        chat_history: Vec<Message>,
+    ) -> GeminiRequestBody {
+        // Combine history + new_message
+        let mut messages_to_send = chat_history;
+        messages_to_send.push(message);
+
+        let mut system_messages = Vec::new();
+        // Gemini's own ids of the calls requested so far, by `ToolCall::id`
+        let mut call_ids: HashMap<&str, &str> = HashMap::new();
+        let mut contents: Vec<GeminiContent> = Vec::with_capacity(messages_to_send.len());
+        for msg in &messages_to_send {
+            if let Message::System(system) = msg
+                && system_message_policy != SystemMessagePolicy::AsUser
+            {
+                system_messages.push(system.content.clone());
+                continue;
+            }
+
+            let mut content = GeminiContent::from(msg);
+            match msg {
+                Message::Assistant(a) => call_ids.extend(a.content.tool_calls().iter().filter_map(
+                    |call| Some((call.id.as_str(), call.call_id.as_deref()?)),
+                )),
+                // Answer a call under the id Gemini gave it, if it gave one
+                Message::Tool(t) => {
+                    for part in &mut content.parts {
+                        if let Some(response) = &mut part.function_response {
+                            response.id = call_ids.get(t.id.as_str()).map(|id| id.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            // Gemini expects turns to alternate, e.g. results of parallel function
+            // calls are answered in a single turn
+            match contents.last_mut() {
+                Some(last) if last.role == content.role => last.parts.extend(content.parts),
+                _ => contents.push(content),
+            }
+        }
+
+        let instructions: Vec<String> = match system_message_policy {
+            SystemMessagePolicy::Merge => model_config
+                .system_instruction
+                .iter()
+                .cloned()
+                .chain(system_messages)
+                .collect(),
+            SystemMessagePolicy::Replace if !system_messages.is_empty() => system_messages,
+            _ => model_config.system_instruction.iter().cloned().collect(),
+        };
+
+        let generation_config = GeminiGenerationConfig::from_model_config(model_config);
+
+        GeminiRequestBody {
+            system_instruction: (!instructions.is_empty()).then(|| SystemInstruction {
+                parts: instructions.into_iter().map(GeminiRequestPart::text).collect(),
+            }),
+            contents,
+            generation_config: Some(generation_config),
+            tools: GeminiTool::from_definitions(&model_config.tools),
+            safety_settings: model_config.safety_settings.clone(),
+        }
+    }
+
+    /// Extract the reply from a `:generateContent` response
+    fn parse_chat_response(gemini_response: GeminiChatResponse) -> Result<ChatResponse> {
+        // Check for API errors in the response
+        if let Some(error) = gemini_response.error {
+            return Err(error.to_error());
+        }
+
+        if let Some(error) = gemini_response.blocked_error() {
+            return Err(error);
+        }
+
+        if gemini_response.candidates.is_empty() {
+            return Err(OrchestraError::invalid_response("No candidates in response"));
+        }
+
+        let candidates: Vec<Candidate> = gemini_response
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(position, candidate)| candidate.to_candidate(position))
+            .collect();
+
+        // Individual candidates may be empty (e.g. blocked), as long as one is usable
+        if candidates.iter().all(|c| c.parts.is_empty()) {
+            return Err(OrchestraError::invalid_response("No content parts in response"));
+        }
+
+        Ok(ChatResponse {
+            usage: gemini_response.usage_metadata.as_ref().map(Usage::from),
+            model_version: gemini_response.model_version.clone(),
+            response_id: gemini_response.response_id.clone(),
+            ..ChatResponse::from_candidates(candidates)
+        })
+    }
+
+    /// Turn a `:streamGenerateContent?alt=sse` HTTP response into a stream of events.
+    ///
+    /// HTTP errors are returned immediately; errors reported inside the event stream
+    /// are yielded as the stream's final item.
+    pub(crate) async fn stream_response(resp: reqwest::Response) -> Result<ChatStream> {
+        if !resp.status().is_success() {
+            return Err(Self::http_error(resp).await);
+        }
+
+        let state = GeminiStreamState {
+            body: Box::pin(resp.bytes_stream()),
+            parser: SseParser::new(),
+            pending: VecDeque::new(),
+            tool_calls: 0,
+            done: false,
+        };
+
+        Ok(Box::pin(futures::stream::unfold(state, GeminiStreamState::next)))
+    }
+}
+
+/// State threaded through the Gemini event stream.
+struct GeminiStreamState {
+    body: std::pin::Pin<Box<dyn Stream<Item = reqwest::Result<bytes::Bytes>> + Send>>,
+    parser: SseParser,
+    /// Events parsed from the body but not yet yielded
+    pending: VecDeque<Result<ChatStreamEvent>>,
+    /// Number of tool calls received so far
+    tool_calls: usize,
+    /// Set once the body ended or an error was queued
+    done: bool,
+}
+
+impl GeminiStreamState {
+    async fn next(mut self) -> Option<(Result<ChatStreamEvent>, Self)> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some((event, self));
+            }
+
+            if self.done {
+                return None;
+            }
+
+            match self.body.next().await {
+                Some(Ok(chunk)) => {
+                    for sse in self.parser.push(&chunk) {
+                        self.queue(&sse);
+                    }
+                }
+                Some(Err(e)) => {
+                    self.pending.push_back(Err(e.into()));
+                    self.done = true;
+                }
+                None => {
+                    if let Some(sse) = self.parser.finish() {
+                        self.queue(&sse);
+                    }
+                    self.done = true;
+                }
+            }
+        }
+    }
+
+    /// Convert one server-sent event into delta events and queue them
+    fn queue(&mut self, sse: &SseEvent) {
+        if self.done {
+            return;
+        }
+
+        match stream_events_from_chunk(&sse.data, &mut self.tool_calls) {
+            Ok(events) => self.pending.extend(events.into_iter().map(Ok)),
+            Err(e) => {
+                self.pending.push_back(Err(e));
+                self.done = true;
+            }
+        }
+    }
+}
+
+/// Convert a single streamed `GeminiChatResponse` chunk into delta events.
+///
+/// Only the first candidate is streamed; with `candidate_count` above 1 the other
+/// candidates are dropped, so use `chat` to get all of them.
+///
+/// `tool_calls` counts the tool calls of earlier chunks and is advanced past the ones
+/// in this chunk.
+fn stream_events_from_chunk(data: &str, tool_calls: &mut usize) -> Result<Vec<ChatStreamEvent>> {
+    let chunk: GeminiChatResponse = serde_json::from_str(data)?;
+
+    if let Some(error) = chunk.error {
+        return Err(error.to_error());
+    }
+
+    if let Some(error) = chunk.prompt_blocked_error() {
+        return Err(error);
+    }
+
+    let mut events = Vec::new();
+
+    if let Some(candidate) = chunk.candidates.first() {
+        events.extend(
+            candidate
+                .content
+                .parts
+                .iter()
+                .filter_map(|part| {
+                    if let Some(data) = &part.inline_data {
+                        return Some(ChatStreamEvent::InlineData {
+                            mime_type: data.mime_type.clone(),
+                            data: data.data.clone(),
+                        });
+                    }
+                    let text = part.text.clone().filter(|text| !text.is_empty())?;
+                    Some(if part.thought {
+                        ChatStreamEvent::ThoughtDelta(text)
+                    } else {
+                        ChatStreamEvent::TextDelta(text)
+                    })
+                }),
+        );
+        let calls = candidate.content.tool_calls_from(*tool_calls);
+        *tool_calls += calls.len();
+        events.extend(calls.into_iter().map(ChatStreamEvent::ToolCall));
+
+        // Gemini repeats usage metadata on every chunk; report it once, at the end
+        if let Some(reason) = candidate.finish_reason() {
+            // Gemini reports `STOP` when it requests function calls
+            let reason = match reason {
+                FinishReason::Stop if *tool_calls > 0 => FinishReason::ToolCalls,
+                reason => reason,
+            };
+            events.push(ChatStreamEvent::Finish(reason));
+            if let Some(usage) = &chunk.usage_metadata {
+                events.push(ChatStreamEvent::Usage(Usage::from(usage)));
+            }
         }
     }
+
+    Ok(events)
 }
 
 #[async_trait]
@@ -35,18 +342,27 @@ impl Provider for GeminiProvider {
     type Config = GeminiConfig;
 
     fn new(config: Self::Config) -> Self {
-        Self { config }
+        let base_url = config.get_base_url();
+        let http = HttpClient::new(&config.base);
+        Self {
+            config,
+            base_url,
+            http,
+        }
     }
 
     fn get_base_url(&self) -> &str {
-        // We'll store the base URL in the provider for efficiency
-        "https://generativelanguage.googleapis.com/v1beta"
+        &self.base_url
     }
 
     fn name(&self) -> &'static str {
         "gemini"
     }
 
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
     fn supports_tools(&self) -> bool {
         true // Gemini supports function calling
     }
@@ -57,7 +373,7 @@ impl Provider for GeminiProvider {
 
     async fn prompt(
         &self,
-        model_config: crate::model::ModelConfig,
+        model_config: ModelConfig,
         prompt: String,
     ) -> Result<ChatResponse> {
         self.chat(model_config, Message::human(prompt), vec![])
@@ -66,24 +382,13 @@ impl Provider for GeminiProvider {
 
     async fn chat(
         &self,
-        model_config: crate::model::ModelConfig,
+        model_config: ModelConfig,
         message: Message,
         chat_history: Vec<Message>,
     ) -> Result<ChatResponse> {
-        let api_key = self.config.get_api_key().ok_or_else(|| {
-            OrchestraError::api_key("API key not found in configuration or environment")
-        })?;
-
-        let client = reqwest::Client::new();
-
-        let mut headers = HeaderMap::new();
-
-        headers.insert("x-goog-api-key", api_key.parse()?);
-        headers.insert("Content-Type", "application/json".parse()?);
+        let client = self.http.get()?;
 
-        // Combine history + new_message
-        let mut messages_to_send = chat_history.clone();
-        messages_to_send.push(message);
+        let headers = self.build_headers()?;
 
         let model_id = &model_config.name;
         let request_url = format!(
@@ -92,82 +397,897 @@ impl Provider for GeminiProvider {
             model_id
         );
 
-        let contents: Vec<GeminiContent> = messages_to_send
-            .iter()
-            .map(|m| GeminiContent::from(m))
-            .collect();
+        let request_body = Self::build_request_body(
+            &model_config,
+            self.config.system_message_policy,
+            message,
+            chat_history,
+        );
 
-        let generation_config = GeminiGenerationConfig::from_model_config(&model_config);
+        let request = client.post(request_url).headers(headers).json(&request_body);
+        let resp = self.retry_policy().send(request, Self::http_error).await?;
 
-        let request_body = GeminiRequestBody {
-            system_instruction: model_config.system_instruction.clone().map(|s| {
-                SystemInstruction {
-                    parts: vec![GeminiRequestPart { text: s }],
-                }
+        let gemini_response: GeminiChatResponse = serde_json::from_str(&resp.text().await?)?;
+
+        Self::parse_chat_response(gemini_response)
+    }
+
+    /// Stream the reply of the first candidate. Other candidates requested with
+    /// `candidate_count` are not streamed; use `chat` to get all of them.
+    async fn chat_stream(
+        &self,
+        model_config: ModelConfig,
+        message: Message,
+        chat_history: Vec<Message>,
+    ) -> Result<ChatStream> {
+        let client = self.http.get()?;
+
+        let headers = self.build_headers()?;
+
+        let request_url = format!(
+            "{}/models/{}:streamGenerateContent?alt=sse",
+            self.get_base_url(),
+            model_config.name
+        );
+
+        let request_body = Self::build_request_body(
+            &model_config,
+            self.config.system_message_policy,
+            message,
+            chat_history,
+        );
+
+        let request = client.post(request_url).headers(headers).json(&request_body);
+        let resp = self.retry_policy().send(request, Self::http_error).await?;
+
+        Self::stream_response(resp).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        messages::{ContentPart, Message, ToolCall, ToolFunction},
+        model::{HarmBlockThreshold, HarmCategory},
+        providers::{
+            test_server::{MockResponse, TestServer},
+            types::{FinishReason, ResponsePart, SafetyRating},
+        },
+        tools::ToolDefinition,
+    };
+
+    /// Request the stand-in server and turn its reply into a Gemini event stream
+    async fn stream_from(server: &TestServer) -> Result<ChatStream> {
+        let resp = reqwest::Client::new()
+            .post(format!(
+                "{}/models/test:streamGenerateContent?alt=sse",
+                server.url()
+            ))
+            .send()
+            .await
+            .unwrap();
+
+        GeminiProvider::stream_response(resp).await
+    }
+
+    #[test]
+    fn test_request_body_with_tools_and_tool_results() {
+        let model_config = ModelConfig::new("gemini-2.5-flash").with_tool(ToolDefinition::new(
+            "get_weather",
+            "Get the current weather for a city",
+            serde_json::json!({
+                "type": "object",
+                "properties": {"city": {"type": "string"}},
+                "required": ["city"]
             }),
-            contents,
-            generation_config: Some(generation_config),
+        ));
+
+        let tool_call = |id: &str, call_id: Option<&str>, city: &str| ToolCall {
+            id: id.to_string(),
+            call_id: call_id.map(str::to_string),
+            function: ToolFunction {
+                name: "get_weather".to_string(),
+                arguments: serde_json::json!({ "city": city }),
+            },
         };
+        let history = vec![
+            Message::human("What's the weather in Paris and Rome?"),
+            Message::assistant_tool_calls(
+                None::<String>,
+                // Only calls Gemini gave an id to are answered by id
+                vec![
+                    tool_call("get_weather_0", None, "Paris"),
+                    tool_call("fc-rome", Some("fc-rome"), "Rome"),
+                ],
+            ),
+            Message::tool(
+                "get_weather_0",
+                "get_weather",
+                serde_json::json!({"temperature": 21, "unit": "celsius"}),
+            ),
+        ];
+        let message = Message::tool("fc-rome", "get_weather", "Sunny, 25 degrees");
 
-        let resp = client
-            .post(request_url)
-            .headers(headers)
-            .json(&request_body)
-            .send()
-            .await?;
+        let body = serde_json::to_value(GeminiProvider::build_request_body(
+            &model_config,
+            SystemMessagePolicy::default(),
+            message,
+            history,
+        ))
+        .unwrap();
 
-        // Check for HTTP errors
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let error_body = resp.text().await.unwrap_or_default();
-            return Err(OrchestraError::provider(
-                "gemini",
-                &format!("HTTP {} error: {}", status, error_body),
-            ));
+        assert_eq!(
+            body["tools"],
+            serde_json::json!([{
+                "functionDeclarations": [{
+                    "name": "get_weather",
+                    "description": "Get the current weather for a city",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {"city": {"type": "string"}},
+                        "required": ["city"]
+                    }
+                }]
+            }])
+        );
+        assert_eq!(
+            body["contents"],
+            serde_json::json!([
+                {"role": "user", "parts": [{"text": "What's the weather in Paris and Rome?"}]},
+                {"role": "model", "parts": [
+                    {"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}},
+                    {"functionCall": {"id": "fc-rome", "name": "get_weather", "args": {"city": "Rome"}}}
+                ]},
+                {"role": "user", "parts": [
+                    {"functionResponse": {
+                        "name": "get_weather",
+                        "response": {"temperature": 21, "unit": "celsius"}
+                    }},
+                    {"functionResponse": {
+                        "id": "fc-rome",
+                        "name": "get_weather",
+                        "response": {"result": "Sunny, 25 degrees"}
+                    }}
+                ]}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_request_body_multimodal() {
+        let message = Message::human_with_parts(vec![
+            ContentPart::text("Summarize the report and describe the chart."),
+            ContentPart::file_uri(
+                "application/pdf",
+                "https://generativelanguage.googleapis.com/v1beta/files/abc-123",
+            ),
+            ContentPart::inline_data("image/png", b"\x89PNG"),
+        ]);
+
+        let body = serde_json::to_value(GeminiProvider::build_request_body(
+            &ModelConfig::new("gemini-2.5-flash"),
+            SystemMessagePolicy::default(),
+            message,
+            vec![],
+        ))
+        .unwrap();
+
+        assert_eq!(
+            body["contents"][0]["parts"],
+            serde_json::json!([
+                {"text": "Summarize the report and describe the chart."},
+                {"fileData": {
+                    "mimeType": "application/pdf",
+                    "fileUri": "https://generativelanguage.googleapis.com/v1beta/files/abc-123"
+                }},
+                {"inlineData": {"mimeType": "image/png", "data": "iVBORw=="}}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_request_body_system_messages() {
+        let model_config = ModelConfig::new("gemini-2.5-flash").with_system_instruction("Be brief.");
+        let history = vec![
+            Message::system("Answer in French."),
+            Message::human("Hi"),
+            Message::human("Are you there?"),
+            Message::assistant("Oui."),
+        ];
+        let body_with = |policy: SystemMessagePolicy| {
+            serde_json::to_value(GeminiProvider::build_request_body(
+                &model_config,
+                policy,
+                Message::human("Capital of Italy?"),
+                history.clone(),
+            ))
+            .unwrap()
+        };
+
+        let body = body_with(SystemMessagePolicy::Merge);
+        assert_eq!(
+            body["system_instruction"],
+            serde_json::json!({"parts": [{"text": "Be brief."}, {"text": "Answer in French."}]})
+        );
+        // Consecutive user turns are merged so roles alternate
+        assert_eq!(
+            body["contents"],
+            serde_json::json!([
+                {"role": "user", "parts": [{"text": "Hi"}, {"text": "Are you there?"}]},
+                {"role": "model", "parts": [{"text": "Oui."}]},
+                {"role": "user", "parts": [{"text": "Capital of Italy?"}]}
+            ])
+        );
+
+        let body = body_with(SystemMessagePolicy::Replace);
+        assert_eq!(
+            body["system_instruction"],
+            serde_json::json!({"parts": [{"text": "Answer in French."}]})
+        );
+
+        let body = body_with(SystemMessagePolicy::Ignore);
+        assert_eq!(
+            body["system_instruction"],
+            serde_json::json!({"parts": [{"text": "Be brief."}]})
+        );
+        assert_eq!(body["contents"].as_array().unwrap().len(), 3);
+
+        let body = body_with(SystemMessagePolicy::AsUser);
+        assert_eq!(
+            body["contents"][0],
+            serde_json::json!({
+                "role": "user",
+                "parts": [{"text": "Answer in French."}, {"text": "Hi"}, {"text": "Are you there?"}]
+            })
+        );
+    }
+
+    #[test]
+    fn test_request_body_without_tools() {
+        let body = serde_json::to_value(GeminiProvider::build_request_body(
+            &ModelConfig::new("gemini-2.5-flash"),
+            SystemMessagePolicy::default(),
+            Message::human("Hi"),
+            vec![],
+        ))
+        .unwrap();
+
+        assert!(body.get("tools").is_none());
+        assert!(body["generationConfig"].get("responseMimeType").is_none());
+    }
+
+    #[test]
+    fn test_request_body_structured_output() {
+        let schema = serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}});
+        let body = serde_json::to_value(GeminiProvider::build_request_body(
+            &ModelConfig::new("gemini-2.5-flash").with_response_schema(schema.clone()),
+            SystemMessagePolicy::default(),
+            Message::human("Where is the Eiffel tower?"),
+            vec![],
+        ))
+        .unwrap();
+
+        assert_eq!(body["generationConfig"]["responseMimeType"], "application/json");
+        assert_eq!(body["generationConfig"]["responseSchema"], schema);
+    }
+
+    #[test]
+    fn test_parse_function_call_response() {
+        let response: GeminiChatResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}},
+                        {"functionCall": {"id": "fc-2", "name": "get_weather", "args": {"city": "Rome"}}}
+                    ]
+                },
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {"promptTokenCount": 30, "candidatesTokenCount": 12, "totalTokenCount": 42},
+            "modelVersion": "gemini-2.5-flash-001",
+            "responseId": "resp-1"
+        }))
+        .unwrap();
+
+        let resp = GeminiProvider::parse_chat_response(response).unwrap();
+
+        assert_eq!(resp.finish_reason, Some(FinishReason::ToolCalls));
+        assert_eq!(resp.usage.as_ref().map(|u| u.total_tokens), Some(42));
+        assert_eq!(resp.model_version.as_deref(), Some("gemini-2.5-flash-001"));
+        assert_eq!(resp.response_id.as_deref(), Some("resp-1"));
+
+        assert!(resp.text.is_empty());
+        assert!(resp.has_tool_calls());
+        assert_eq!(resp.tool_calls.len(), 2);
+        assert_eq!(resp.tool_calls[0].id, "get_weather_0");
+        assert_eq!(resp.tool_calls[0].function.name, "get_weather");
+        assert_eq!(
+            resp.tool_calls[0].function.arguments,
+            serde_json::json!({"city": "Paris"})
+        );
+        assert_eq!(resp.tool_calls[1].id, "fc-2");
+        assert_eq!(resp.tool_calls[1].call_id, Some("fc-2".to_string()));
+    }
+
+    #[test]
+    fn test_parse_multiple_candidates() {
+        let body = serde_json::to_value(GeminiProvider::build_request_body(
+            &ModelConfig::new("gemini-2.5-flash").with_candidate_count(2).unwrap(),
+            SystemMessagePolicy::default(),
+            Message::human("Pick a number"),
+            vec![],
+        ))
+        .unwrap();
+        assert_eq!(body["generationConfig"]["candidateCount"], 2);
+
+        let response: GeminiChatResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [
+                {
+                    "content": {"role": "model", "parts": [{"text": "7"}]},
+                    "finishReason": "STOP",
+                    "index": 0,
+                    "safetyRatings": [
+                        {"category": "HARM_CATEGORY_HARASSMENT", "probability": "NEGLIGIBLE"}
+                    ]
+                },
+                {
+                    "finishReason": "SAFETY",
+                    "index": 1,
+                    "safetyRatings": [
+                        {"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "probability": "HIGH", "blocked": true}
+                    ]
+                }
+            ]
+        }))
+        .unwrap();
+
+        let resp = GeminiProvider::parse_chat_response(response).unwrap();
+
+        assert_eq!(resp.text, "7");
+        assert_eq!(resp.candidates.len(), 2);
+        assert_eq!(resp.candidates[0].safety_ratings[0].probability, "NEGLIGIBLE");
+        assert!(!resp.candidates[0].safety_ratings[0].blocked);
+
+        let blocked = &resp.candidates[1];
+        assert_eq!(blocked.index, 1);
+        assert!(blocked.text.is_empty());
+        assert_eq!(blocked.finish_reason, Some(FinishReason::Safety));
+        assert_eq!(
+            blocked.safety_ratings,
+            vec![SafetyRating {
+                category: "HARM_CATEGORY_DANGEROUS_CONTENT".to_string(),
+                probability: "HIGH".to_string(),
+                blocked: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_first_candidate_blocked() {
+        let response: GeminiChatResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [
+                {"finishReason": "SAFETY", "index": 0},
+                {
+                    "content": {"role": "model", "parts": [{"text": "42"}]},
+                    "finishReason": "STOP",
+                    "index": 1
+                }
+            ]
+        }))
+        .unwrap();
+
+        let resp = GeminiProvider::parse_chat_response(response).unwrap();
+
+        // The primary reply is the first candidate with content
+        assert_eq!(resp.text, "42");
+        assert_eq!(resp.finish_reason, Some(FinishReason::Stop));
+        assert_eq!(resp.candidates.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_all_response_parts() {
+        let response: GeminiChatResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "The user wants a chart.", "thought": true},
+                        {"functionCall": {"name": "render_chart", "args": {"kind": "bar"}}},
+                        {"text": "Here is the chart:"},
+                        {"inlineData": {"mimeType": "image/png", "data": "iVBORw0KGgo="}},
+                        {"text": " sales doubled."}
+                    ]
+                },
+                "finishReason": "STOP"
+            }]
+        }))
+        .unwrap();
+
+        let resp = GeminiProvider::parse_chat_response(response).unwrap();
+
+        assert_eq!(resp.text, "Here is the chart: sales doubled.");
+        assert_eq!(resp.tool_calls.len(), 1);
+        assert_eq!(resp.parts.len(), 5);
+        assert_eq!(
+            resp.parts[0],
+            ResponsePart::Thought("The user wants a chart.".to_string())
+        );
+        assert!(matches!(&resp.parts[1], ResponsePart::ToolCall(call) if call.function.name == "render_chart"));
+        assert_eq!(resp.parts[2].as_text(), Some("Here is the chart:"));
+        assert_eq!(
+            resp.parts[3],
+            ResponsePart::InlineData {
+                mime_type: "image/png".to_string(),
+                data: "iVBORw0KGgo=".to_string(),
+            }
+        );
+        assert_eq!(ResponsePart::concat_text(&resp.parts), resp.text);
+    }
+
+    #[test]
+    fn test_request_body_thinking_config() {
+        let thinking_config = |config: ModelConfig| {
+            serde_json::to_value(GeminiProvider::build_request_body(
+                &config,
+                SystemMessagePolicy::default(),
+                Message::human("Hi"),
+                vec![],
+            ))
+            .unwrap()["generationConfig"]
+                .get("thinkingConfig")
+                .cloned()
+        };
+
+        assert_eq!(thinking_config(ModelConfig::new("gemini-2.5-flash")), None);
+        assert_eq!(
+            thinking_config(ModelConfig::new("gemini-2.5-flash").with_thinking_mode(false)),
+            Some(serde_json::json!({"thinkingBudget": 0}))
+        );
+        assert_eq!(
+            thinking_config(
+                ModelConfig::new("gemini-2.5-flash")
+                    .with_thinking_mode(true)
+                    .with_thinking_budget(1024)
+                    .with_include_thoughts(true)
+            ),
+            Some(serde_json::json!({"thinkingBudget": 1024, "includeThoughts": true}))
+        );
+        // The default model doesn't think unless asked to, so enabling thinking mode
+        // without a budget requests dynamic thinking
+        assert_eq!(
+            thinking_config(ModelConfig::new(PREDEFINED_MODELS[0]).with_thinking_mode(true)),
+            Some(serde_json::json!({"thinkingBudget": -1}))
+        );
+    }
+
+    #[test]
+    fn test_parse_thoughts() {
+        let response: GeminiChatResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "Comparing the two options. ", "thought": true},
+                        {"text": "Picking the cheaper one.", "thought": true},
+                        {"text": "Go with option B."}
+                    ]
+                },
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 10,
+                "candidatesTokenCount": 6,
+                "thoughtsTokenCount": 120,
+                "totalTokenCount": 136
+            }
+        }))
+        .unwrap();
+
+        let resp = GeminiProvider::parse_chat_response(response).unwrap();
+
+        assert_eq!(resp.text, "Go with option B.");
+        assert_eq!(
+            resp.thought_summary().as_deref(),
+            Some("Comparing the two options. Picking the cheaper one.")
+        );
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.completion_tokens, 6);
+        assert_eq!(usage.thoughts_tokens, Some(120));
+    }
+
+    #[test]
+    fn test_request_body_safety_settings() {
+        let model_config = ModelConfig::new("gemini-2.5-flash")
+            .with_safety_setting(HarmCategory::Harassment, HarmBlockThreshold::BlockOnlyHigh)
+            .with_safety_setting(HarmCategory::DangerousContent, HarmBlockThreshold::BlockNone)
+            .with_safety_setting(HarmCategory::Harassment, HarmBlockThreshold::BlockLowAndAbove);
+
+        let body = serde_json::to_value(GeminiProvider::build_request_body(
+            &model_config,
+            SystemMessagePolicy::default(),
+            Message::human("Hi"),
+            vec![],
+        ))
+        .unwrap();
+
+        assert_eq!(
+            body["safetySettings"],
+            serde_json::json!([
+                {"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "threshold": "BLOCK_NONE"},
+                {"category": "HARM_CATEGORY_HARASSMENT", "threshold": "BLOCK_LOW_AND_ABOVE"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_blocked_prompt() {
+        let response: GeminiChatResponse = serde_json::from_value(serde_json::json!({
+            "promptFeedback": {
+                "blockReason": "SAFETY",
+                "safetyRatings": [
+                    {"category": "HARM_CATEGORY_HARASSMENT", "probability": "NEGLIGIBLE"},
+                    {"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "probability": "HIGH"}
+                ]
+            }
+        }))
+        .unwrap();
+
+        match GeminiProvider::parse_chat_response(response) {
+            Err(OrchestraError::ContentBlocked { reason, categories }) => {
+                assert_eq!(reason, "SAFETY");
+                assert_eq!(categories, vec!["HARM_CATEGORY_DANGEROUS_CONTENT"]);
+            }
+            other => panic!("Expected content blocked error, got {:?}", other),
         }
+    }
 
-        let response_body = resp.text().await?;
+    #[test]
+    fn test_parse_blocked_candidate() {
+        let response: GeminiChatResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "finishReason": "SAFETY",
+                "safetyRatings": [
+                    {"category": "HARM_CATEGORY_HARASSMENT", "probability": "MEDIUM", "blocked": true},
+                    {"category": "HARM_CATEGORY_HATE_SPEECH", "probability": "MEDIUM"}
+                ]
+            }]
+        }))
+        .unwrap();
 
-        println!("Response body: {}", response_body);
+        match GeminiProvider::parse_chat_response(response) {
+            Err(OrchestraError::ContentBlocked { reason, categories }) => {
+                assert_eq!(reason, "SAFETY");
+                assert_eq!(categories, vec!["HARM_CATEGORY_HARASSMENT"]);
+            }
+            other => panic!("Expected content blocked error, got {:?}", other),
+        }
+    }
 
-        let gemini_response: GeminiChatResponse = serde_json::from_str(&response_body)?;
+    #[test]
+    fn test_parse_truncated_response() {
+        let response: GeminiChatResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [{"text": "Once upon a"}]},
+                "finishReason": "MAX_TOKENS"
+            }]
+        }))
+        .unwrap();
 
-        // Check for API errors in the response
-        if let Some(error) = gemini_response.error {
-            return Err(OrchestraError::provider(
-                "gemini",
-                &format!(
-                    "API error {}: {} ({})",
-                    error.code, error.message, error.status
-                ),
-            ));
+        let resp = GeminiProvider::parse_chat_response(response).unwrap();
+
+        assert_eq!(resp.text, "Once upon a");
+        assert_eq!(resp.finish_reason, Some(FinishReason::MaxTokens));
+        assert!(resp.is_truncated());
+        assert!(resp.usage.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_response_parses_split_chunks() {
+        let body = concat!(
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"Hel\"}],\"role\": \"model\"},\"index\": 0}]}\r\n\r\n",
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"lo wörld\"}],\"role\": \"model\"},\"index\": 0}]}\r\n\r\n",
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"!\"}],\"role\": \"model\"},\"finishReason\": \"STOP\",\"index\": 0}],",
+            "\"usageMetadata\": {\"promptTokenCount\": 4,\"candidatesTokenCount\": 3,\"totalTokenCount\": 7}}\r\n\r\n",
+        )
+        .as_bytes();
+
+        // Split the body at awkward places: mid-line, mid-JSON and inside a UTF-8 character
+        let umlaut = body.iter().position(|&b| b == 0xC3).unwrap() + 1;
+        let server = TestServer::start(vec![MockResponse::sse(vec![
+            body[..7].to_vec(),
+            body[7..umlaut].to_vec(),
+            body[umlaut..body.len() - 3].to_vec(),
+            body[body.len() - 3..].to_vec(),
+        ])])
+        .await;
+
+        let events: Vec<ChatStreamEvent> = stream_from(&server)
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(
+            events,
+            vec![
+                ChatStreamEvent::TextDelta("Hel".to_string()),
+                ChatStreamEvent::TextDelta("lo wörld".to_string()),
+                ChatStreamEvent::TextDelta("!".to_string()),
+                ChatStreamEvent::Finish(FinishReason::Stop),
+                ChatStreamEvent::Usage(Usage {
+                    prompt_tokens: 4,
+                    completion_tokens: 3,
+                    total_tokens: 7,
+                    thoughts_tokens: None,
+                }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_response_thoughts() {
+        let server = TestServer::start(vec![MockResponse::sse(vec![
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"Thinking...\", \"thought\": true}],\"role\": \"model\"}}]}\r\n\r\n",
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"Done.\"}],\"role\": \"model\"},\"finishReason\": \"STOP\"}]}\r\n\r\n",
+        ])])
+        .await;
+
+        let events: Vec<ChatStreamEvent> = stream_from(&server)
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(
+            events,
+            vec![
+                ChatStreamEvent::ThoughtDelta("Thinking...".to_string()),
+                ChatStreamEvent::TextDelta("Done.".to_string()),
+                ChatStreamEvent::Finish(FinishReason::Stop),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_response_inline_data_and_first_candidate() {
+        let server = TestServer::start(vec![MockResponse::sse(vec![
+            "data: {\"candidates\": [{\"index\": 0, \"content\": {\"parts\": [{\"text\": \"Here it is:\"}, {\"inlineData\": {\"mimeType\": \"image/png\", \"data\": \"iVBORw0K\"}}],\"role\": \"model\"}}, {\"index\": 1, \"content\": {\"parts\": [{\"text\": \"Another take\"}],\"role\": \"model\"}}]}\r\n\r\n",
+            "data: {\"candidates\": [{\"index\": 0, \"content\": {\"parts\": [{\"text\": \"\"}],\"role\": \"model\"},\"finishReason\": \"STOP\"}]}\r\n\r\n",
+        ])])
+        .await;
+
+        let events: Vec<ChatStreamEvent> = stream_from(&server)
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        // Only the first candidate is streamed
+        assert_eq!(
+            events,
+            vec![
+                ChatStreamEvent::TextDelta("Here it is:".to_string()),
+                ChatStreamEvent::InlineData {
+                    mime_type: "image/png".to_string(),
+                    data: "iVBORw0K".to_string(),
+                },
+                ChatStreamEvent::Finish(FinishReason::Stop),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_response_tool_calls() {
+        let server = TestServer::start(vec![MockResponse::sse(vec![
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"functionCall\": {\"name\": \"get_weather\", \"args\": {\"city\": \"Paris\"}}}],\"role\": \"model\"}}]}\r\n\r\n",
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"functionCall\": {\"name\": \"get_weather\", \"args\": {\"city\": \"Rome\"}}}],\"role\": \"model\"},\"finishReason\": \"STOP\"}]}\r\n\r\n",
+        ])])
+        .await;
+
+        let events: Vec<ChatStreamEvent> = stream_from(&server)
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        let ids: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match event {
+                ChatStreamEvent::ToolCall(call) => Some(call.id.as_str()),
+                _ => None,
+            })
+            .collect();
+        // Ids stay unique across chunks
+        assert_eq!(ids, vec!["get_weather_0", "get_weather_1"]);
+        assert_eq!(events.last(), Some(&ChatStreamEvent::Finish(FinishReason::ToolCalls)));
+    }
+
+    #[tokio::test]
+    async fn test_stream_response_mid_stream_error() {
+        let server = TestServer::start(vec![MockResponse::sse(vec![
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"Partial\"}],\"role\": \"model\"}}]}\n\n",
+            "data: {\"error\": {\"code\": 503,\"message\": \"The model is overloaded.\",\"status\": \"UNAVAILABLE\"}}\n\n",
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"ignored\"}],\"role\": \"model\"}}]}\n\n",
+        ])])
+        .await;
+
+        let events: Vec<Result<ChatStreamEvent>> = stream_from(&server).await.unwrap().collect().await;
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].as_ref().unwrap(),
+            &ChatStreamEvent::TextDelta("Partial".to_string())
+        );
+        match &events[1] {
+            Err(OrchestraError::Provider {
+                provider,
+                message,
+                status,
+                ..
+            }) => {
+                assert_eq!(provider, "gemini");
+                assert!(message.contains("overloaded"));
+                assert_eq!(*status, Some(503));
+            }
+            other => panic!("Expected provider error, got {:?}", other),
         }
+    }
 
-        // Better error handling for response structure
-        let candidate = gemini_response
-            .candidates
-            .first()
-            .ok_or_else(|| OrchestraError::invalid_response("No candidates in response"))?;
+    #[tokio::test]
+    async fn test_stream_response_http_error() {
+        let server = TestServer::start(vec![MockResponse::status(
+            400,
+            serde_json::json!({"error": {"code": 400, "message": "Bad request", "status": "INVALID_ARGUMENT"}}),
+        )])
+        .await;
 
-        let part = candidate
-            .content
-            .parts
-            .first()
-            .ok_or_else(|| OrchestraError::invalid_response("No parts in response content"))?;
+        let result = stream_from(&server).await;
 
-        let text = part
-            .text
-            .as_ref()
-            .ok_or_else(|| OrchestraError::invalid_response("No text in response part"))?;
+        match result {
+            Err(OrchestraError::InvalidRequest { message, body, .. }) => {
+                assert_eq!(message, "Bad request");
+                assert!(body.unwrap().contains("INVALID_ARGUMENT"));
+            }
+            Err(other) => panic!("Expected invalid request error, got {:?}", other),
+            Ok(_) => panic!("Expected invalid request error, got a stream"),
+        }
+    }
 
-        Ok(ChatResponse { text: text.clone() })
+    #[test]
+    fn test_base_url() {
+        let provider = GeminiProvider::with_default_config();
+        assert_eq!(
+            provider.get_base_url(),
+            "https://generativelanguage.googleapis.com/v1beta"
+        );
+
+        let provider = GeminiProvider::new(GeminiConfig::new().with_beta(false));
+        assert_eq!(
+            provider.get_base_url(),
+            "https://generativelanguage.googleapis.com/v1"
+        );
+
+        let provider = GeminiProvider::new(
+            GeminiConfig::new()
+                .with_base_url("https://egress.example.com/gemini/")
+                .with_api_version("v1alpha"),
+        );
+        assert_eq!(
+            provider.get_base_url(),
+            "https://egress.example.com/gemini/v1alpha"
+        );
+
+        // A base URL that already names the version is not given a second one
+        let provider = GeminiProvider::new(
+            GeminiConfig::new().with_base_url("https://generativelanguage.googleapis.com/v1beta/"),
+        );
+        assert_eq!(
+            provider.get_base_url(),
+            "https://generativelanguage.googleapis.com/v1beta"
+        );
+
+        let provider = GeminiProvider::new(
+            GeminiConfig::new().with_base_url("https://egress.example.com/vertex"),
+        );
+        assert_eq!(
+            provider.get_base_url(),
+            "https://egress.example.com/vertex/v1beta"
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::messages::Message;
+    #[tokio::test]
+    async fn test_chat_request() {
+        let server = TestServer::start(vec![MockResponse::json(serde_json::json!({
+            "candidates": [{
+                "content": {"parts": [{"text": "Paris."}], "role": "model"},
+                "finishReason": "STOP"
+            }]
+        }))])
+        .await;
+        let provider = GeminiProvider::new(
+            GeminiConfig::new()
+                .with_api_key("test-key")
+                .with_base_url(server.url())
+                .with_beta(false)
+                .with_header("X-Egress-Token", "secret"),
+        );
+
+        let resp = provider
+            .prompt(ModelConfig::new("gemini-2.5-flash"), "Capital of France?".to_string())
+            .await
+            .unwrap();
+        assert_eq!(resp.text, "Paris.");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/models/gemini-2.5-flash:generateContent");
+        assert_eq!(requests[0].header("x-goog-api-key"), Some("test-key"));
+        assert_eq!(requests[0].header("x-egress-token"), Some("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_chat_retries_transient_errors() {
+        let server = TestServer::start(vec![
+            MockResponse::status(
+                429,
+                serde_json::json!({"error": {"code": 429, "message": "Resource exhausted", "status": "RESOURCE_EXHAUSTED"}}),
+            )
+            .with_header("Retry-After", "0"),
+            MockResponse::json(serde_json::json!({
+                "candidates": [{"content": {"parts": [{"text": "Hi!"}], "role": "model"}}]
+            })),
+        ])
+        .await;
+        let provider = GeminiProvider::new(
+            GeminiConfig::new()
+                .with_api_key("test-key")
+                .with_base_url(server.url()),
+        );
+
+        let resp = provider
+            .prompt(ModelConfig::new("gemini-2.5-flash"), "Hello".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(resp.text, "Hi!");
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_files_use_provider_settings() {
+        let server = TestServer::start(vec![
+            MockResponse::status(503, serde_json::json!({"error": {"code": 503}}))
+                .with_header("Retry-After", "0"),
+            MockResponse::json(serde_json::json!({
+                "name": "files/abc-123",
+                "mimeType": "application/pdf",
+                "uri": "https://example.com/files/abc-123",
+                "state": "ACTIVE"
+            })),
+        ])
+        .await;
+        // The Files API only exists in v1beta, even when the stable API is selected
+        let provider = GeminiProvider::new(
+            GeminiConfig::new()
+                .with_api_key("test-key")
+                .with_base_url(server.url())
+                .with_beta(false)
+                .with_header("X-Egress-Token", "secret"),
+        );
+
+        let file = provider.files().unwrap().get("abc-123").await.unwrap();
+        assert!(file.is_active());
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].path, "/v1beta/files/abc-123");
+        assert_eq!(requests[1].header("x-egress-token"), Some("secret"));
+    }
 
     #[tokio::test]
     async fn test_prompt() {
@@ -1,5 +1,7 @@
+mod files;
 mod r#impl;
 mod types;
 
+pub use files::*;
 pub use r#impl::*;
 pub use types::*;
@@ -1,6 +1,14 @@
 use serde::{Deserialize, Serialize};
 
-use crate::messages::Message;
+use crate::{
+    error::OrchestraError,
+    messages::{
+        ContentPart, Message, MessageContent, ToolCall, ToolFunction, ToolMessage, ToolResultContent,
+    },
+    model::SafetySetting,
+    providers::types::{Candidate, FinishReason, ResponsePart, SafetyRating, Usage},
+    tools::ToolDefinition,
+};
 
 pub const PREDEFINED_MODELS: &[&str] = &[
     "gemini-2.5-flash-lite",
@@ -17,6 +25,44 @@ pub struct GeminiRequestBody {
     pub contents: Vec<GeminiContent>,
     #[serde(rename = "generationConfig", skip_serializing_if = "Option::is_none")]
     pub generation_config: Option<GeminiGenerationConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<GeminiTool>>,
+    #[serde(rename = "safetySettings", skip_serializing_if = "Vec::is_empty", default)]
+    pub safety_settings: Vec<SafetySetting>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiTool {
+    #[serde(rename = "functionDeclarations")]
+    pub function_declarations: Vec<GeminiFunctionDeclaration>,
+}
+
+impl GeminiTool {
+    /// Wrap tool definitions in a single Gemini tool, or `None` if there are none
+    pub fn from_definitions(tools: &[ToolDefinition]) -> Option<Vec<GeminiTool>> {
+        if tools.is_empty() {
+            return None;
+        }
+
+        Some(vec![GeminiTool {
+            function_declarations: tools
+                .iter()
+                .map(|tool| GeminiFunctionDeclaration {
+                    name: tool.name.clone(),
+                    description: tool.description.clone(),
+                    parameters: tool.has_parameters().then(|| tool.parameters.clone()),
+                })
+                .collect(),
+        }])
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiFunctionDeclaration {
+    pub name: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,9 +76,53 @@ pub struct GeminiContent {
     pub parts: Vec<GeminiRequestPart>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GeminiRequestPart {
-    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(rename = "functionCall", skip_serializing_if = "Option::is_none")]
+    pub function_call: Option<GeminiFunctionCall>,
+    #[serde(rename = "functionResponse", skip_serializing_if = "Option::is_none")]
+    pub function_response: Option<GeminiFunctionResponse>,
+    #[serde(rename = "inlineData", skip_serializing_if = "Option::is_none")]
+    pub inline_data: Option<GeminiInlineData>,
+    #[serde(rename = "fileData", skip_serializing_if = "Option::is_none")]
+    pub file_data: Option<GeminiFileData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiFileData {
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    #[serde(rename = "fileUri")]
+    pub file_uri: String,
+}
+
+impl GeminiRequestPart {
+    /// Create a text part
+    pub fn text<S: Into<String>>(text: S) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiFunctionCall {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub args: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiFunctionResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    pub response: serde_json::Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +137,59 @@ pub struct GeminiGenerationConfig {
     pub max_output_tokens: Option<u32>,
     #[serde(rename = "stopSequences", skip_serializing_if = "Option::is_none")]
     pub stop_sequences: Option<Vec<String>>,
+    #[serde(rename = "candidateCount", skip_serializing_if = "Option::is_none")]
+    pub candidate_count: Option<u32>,
+    #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
+    pub response_mime_type: Option<String>,
+    #[serde(rename = "responseSchema", skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<serde_json::Value>,
+    #[serde(rename = "thinkingConfig", skip_serializing_if = "Option::is_none")]
+    pub thinking_config: Option<GeminiThinkingConfig>,
+}
+
+/// Thinking settings for 2.5 models
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiThinkingConfig {
+    /// Maximum tokens to spend on thinking; `0` disables thinking and `-1` lets the
+    /// model decide
+    #[serde(rename = "thinkingBudget", skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<i32>,
+    /// Whether to return thought summaries
+    #[serde(rename = "includeThoughts", skip_serializing_if = "Option::is_none")]
+    pub include_thoughts: Option<bool>,
+}
+
+impl GeminiThinkingConfig {
+    /// Budget that lets the model decide how much to think
+    pub const DYNAMIC_BUDGET: i32 = -1;
+
+    /// Build the thinking settings, or `None` to leave the model's defaults in place.
+    ///
+    /// Some models, like `gemini-2.5-flash-lite`, don't think by default, so enabling
+    /// thinking mode without a budget asks for dynamic thinking.
+    pub fn from_model_config(config: &crate::model::ModelConfig) -> Option<Self> {
+        let thinking_budget = config
+            .thinking_budget
+            .map(|budget| i32::try_from(budget).unwrap_or(i32::MAX));
+
+        let thinking_config = match config.thinking_mode {
+            Some(false) => Self {
+                thinking_budget: Some(0),
+                include_thoughts: None,
+            },
+            Some(true) => Self {
+                thinking_budget: thinking_budget.or(Some(Self::DYNAMIC_BUDGET)),
+                include_thoughts: config.include_thoughts,
+            },
+            None => Self {
+                thinking_budget,
+                include_thoughts: config.include_thoughts,
+            },
+        };
+
+        (thinking_config.thinking_budget.is_some() || thinking_config.include_thoughts.is_some())
+            .then_some(thinking_config)
+    }
 }
 
 impl GeminiGenerationConfig {
@@ -61,6 +204,75 @@ impl GeminiGenerationConfig {
             } else {
                 Some(config.stop_sequences.clone())
             },
+            candidate_count: config.candidate_count,
+            response_mime_type: config.response_mime_type.clone(),
+            response_schema: config.response_schema.clone(),
+            thinking_config: GeminiThinkingConfig::from_model_config(config),
+        }
+    }
+}
+
+impl From<&MessageContent> for Vec<GeminiRequestPart> {
+    fn from(content: &MessageContent) -> Self {
+        match content {
+            MessageContent::Text(text) => vec![GeminiRequestPart::text(text.clone())],
+            MessageContent::Mixed { text, tool_calls } => text
+                .iter()
+                .map(|t| GeminiRequestPart::text(t.clone()))
+                .chain(tool_calls.iter().map(|call| GeminiRequestPart {
+                    function_call: Some(GeminiFunctionCall {
+                        id: call.call_id.clone(),
+                        name: call.function.name.clone(),
+                        args: call.function.arguments.clone(),
+                    }),
+                    ..Default::default()
+                }))
+                .collect(),
+            MessageContent::Parts(parts) => parts.iter().map(GeminiRequestPart::from).collect(),
+        }
+    }
+}
+
+impl From<&ContentPart> for GeminiRequestPart {
+    fn from(part: &ContentPart) -> Self {
+        match part {
+            ContentPart::Text(text) => GeminiRequestPart::text(text.clone()),
+            ContentPart::InlineData { mime_type, data } => GeminiRequestPart {
+                inline_data: Some(GeminiInlineData {
+                    mime_type: mime_type.clone(),
+                    data: data.clone(),
+                }),
+                ..Default::default()
+            },
+            ContentPart::FileData {
+                mime_type,
+                file_uri,
+            } => GeminiRequestPart {
+                file_data: Some(GeminiFileData {
+                    mime_type: mime_type.clone(),
+                    file_uri: file_uri.clone(),
+                }),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl From<&ToolMessage> for GeminiRequestPart {
+    fn from(msg: &ToolMessage) -> Self {
+        // Gemini expects the response to be a JSON object
+        let response = match &msg.content {
+            ToolResultContent::Json(value) if value.is_object() => value.clone(),
+            content => serde_json::json!({ "result": content.to_json() }),
+        };
+
+        GeminiRequestPart {
+            function_response: Some(GeminiFunctionResponse {
+                id: None,
+                name: msg.name.clone(),
+                response,
+            }),
+            ..Default::default()
         }
     }
 }
@@ -70,21 +282,21 @@ impl From<&Message> for GeminiContent {
         match msg {
             Message::Human(h) => GeminiContent {
                 role: "user".to_string(),
-                parts: vec![GeminiRequestPart {
-                    text: h.content.to_text(),
-                }],
+                parts: (&h.content).into(),
             },
             Message::Assistant(a) => GeminiContent {
                 role: "model".to_string(),
-                parts: vec![GeminiRequestPart {
-                    text: a.content.to_text(),
-                }],
+                parts: (&a.content).into(),
             },
+            // Gemini has no system role; see `SystemMessagePolicy` for the alternatives
             Message::System(s) => GeminiContent {
-                role: "system".to_string(),
-                parts: vec![GeminiRequestPart {
-                    text: s.content.clone(),
-                }],
+                role: "user".to_string(),
+                parts: vec![GeminiRequestPart::text(s.content.clone())],
+            },
+            // Function responses are sent back on the user's turn
+            Message::Tool(t) => GeminiContent {
+                role: "user".to_string(),
+                parts: vec![t.into()],
             },
         }
     }
@@ -92,6 +304,7 @@ impl From<&Message> for GeminiContent {
 
 #[derive(Debug, Deserialize)]
 pub struct GeminiChatResponse {
+    #[serde(default)]
     pub candidates: Vec<GeminiCandidate>,
     #[serde(rename = "usageMetadata")]
     pub usage_metadata: Option<UsageMetadata>,
@@ -99,9 +312,87 @@ pub struct GeminiChatResponse {
     pub model_version: Option<String>,
     #[serde(rename = "responseId")]
     pub response_id: Option<String>,
+    #[serde(rename = "promptFeedback")]
+    pub prompt_feedback: Option<GeminiPromptFeedback>,
     pub error: Option<GeminiError>,
 }
 
+impl GeminiChatResponse {
+    /// The error to report if the prompt was blocked
+    pub fn prompt_blocked_error(&self) -> Option<OrchestraError> {
+        let feedback = self.prompt_feedback.as_ref()?;
+        let reason = feedback.block_reason.as_ref()?;
+
+        Some(OrchestraError::content_blocked(
+            reason.clone(),
+            blocked_categories(&feedback.safety_ratings),
+        ))
+    }
+
+    /// The error to report if the prompt, or every candidate, was blocked
+    pub fn blocked_error(&self) -> Option<OrchestraError> {
+        if let Some(error) = self.prompt_blocked_error() {
+            return Some(error);
+        }
+
+        let blocked = !self.candidates.is_empty()
+            && self.candidates.iter().all(|candidate| {
+                candidate.content.parts.is_empty()
+                    && candidate.finish_reason() == Some(FinishReason::Safety)
+            });
+        if !blocked {
+            return None;
+        }
+
+        let candidate = &self.candidates[0];
+        Some(OrchestraError::content_blocked(
+            candidate.finish_reason.clone().unwrap_or_default(),
+            blocked_categories(&candidate.safety_ratings),
+        ))
+    }
+}
+
+/// Why the prompt was blocked, if it was
+#[derive(Debug, Deserialize)]
+pub struct GeminiPromptFeedback {
+    #[serde(rename = "blockReason")]
+    pub block_reason: Option<String>,
+    #[serde(rename = "safetyRatings", default)]
+    pub safety_ratings: Vec<GeminiSafetyRating>,
+}
+
+/// Categories flagged as blocked, or with a medium or high probability of harm if
+/// none is flagged
+fn blocked_categories(ratings: &[GeminiSafetyRating]) -> Vec<String> {
+    let flagged: Vec<String> = ratings
+        .iter()
+        .filter(|rating| rating.blocked)
+        .map(|rating| rating.category.clone())
+        .collect();
+    if !flagged.is_empty() {
+        return flagged;
+    }
+
+    ratings
+        .iter()
+        .filter(|rating| matches!(rating.probability.as_str(), "MEDIUM" | "HIGH"))
+        .map(|rating| rating.category.clone())
+        .collect()
+}
+
+/// Body of an unsuccessful Gemini HTTP response
+#[derive(Debug, Deserialize)]
+pub struct GeminiErrorResponse {
+    pub error: GeminiError,
+}
+
+impl GeminiErrorResponse {
+    /// The message of an error body, if it has the expected shape
+    pub fn parse_message(body: &str) -> Option<String> {
+        serde_json::from_str::<Self>(body).ok().map(|e| e.error.message)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GeminiError {
     pub code: u32,
@@ -109,33 +400,177 @@ pub struct GeminiError {
     pub status: String,
 }
 
+impl GeminiError {
+    /// The error to report for an error returned inside the body of a response to a
+    /// model request
+    pub fn to_error(&self) -> OrchestraError {
+        OrchestraError::from_status(
+            "gemini",
+            self.code as u16,
+            format!("{} ({})", self.message, self.status),
+            None,
+        )
+        .into_model_error()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GeminiCandidate {
+    #[serde(default)]
     pub content: GeminiContentResponse,
     #[serde(rename = "finishReason")]
     pub finish_reason: Option<String>,
     pub index: Option<u32>,
+    #[serde(rename = "safetyRatings", default)]
+    pub safety_ratings: Vec<GeminiSafetyRating>,
 }
 
 #[derive(Debug, Deserialize)]
+pub struct GeminiSafetyRating {
+    pub category: String,
+    #[serde(default)]
+    pub probability: String,
+    #[serde(default)]
+    pub blocked: bool,
+}
+
+impl From<&GeminiSafetyRating> for SafetyRating {
+    fn from(rating: &GeminiSafetyRating) -> Self {
+        SafetyRating {
+            category: rating.category.clone(),
+            probability: rating.probability.clone(),
+            blocked: rating.blocked,
+        }
+    }
+}
+
+impl GeminiCandidate {
+    /// Get the normalized finish reason, if the candidate has one
+    pub fn finish_reason(&self) -> Option<FinishReason> {
+        self.finish_reason.as_deref().map(|reason| match reason {
+            "STOP" => FinishReason::Stop,
+            "MAX_TOKENS" => FinishReason::MaxTokens,
+            "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" => {
+                FinishReason::Safety
+            }
+            other => FinishReason::Other(other.to_string()),
+        })
+    }
+
+    /// Convert into a provider-independent candidate. `position` is used when
+    /// Gemini omits the candidate's index.
+    pub fn to_candidate(&self, position: usize) -> Candidate {
+        let mut candidate =
+            Candidate::from_parts(self.index.unwrap_or(position as u32), self.content.parts());
+
+        // Gemini reports `STOP` when it requests function calls
+        candidate.finish_reason = match self.finish_reason() {
+            Some(FinishReason::Stop) if !candidate.tool_calls.is_empty() => {
+                Some(FinishReason::ToolCalls)
+            }
+            reason => reason,
+        };
+        candidate.safety_ratings = self.safety_ratings.iter().map(SafetyRating::from).collect();
+
+        candidate
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
 pub struct GeminiContentResponse {
+    #[serde(default)]
     pub parts: Vec<GeminiPartResponse>,
+    #[serde(default)]
     pub role: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct GeminiPartResponse {
     pub text: Option<String>,
+    /// Marks `text` as a thought summary rather than answer text
+    #[serde(default)]
+    pub thought: bool,
+    #[serde(rename = "functionCall")]
+    pub function_call: Option<GeminiFunctionCall>,
+    #[serde(rename = "inlineData")]
+    pub inline_data: Option<GeminiInlineData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiInlineData {
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    /// Base64-encoded bytes
+    pub data: String,
+}
+
+impl GeminiContentResponse {
+    /// Convert every part into a typed response part, preserving their order.
+    ///
+    /// Parts of kinds this crate does not model yet are skipped.
+    pub fn parts(&self) -> Vec<ResponsePart> {
+        let mut tool_calls = self.tool_calls().into_iter();
+
+        self.parts
+            .iter()
+            .filter_map(|part| {
+                if part.function_call.is_some() {
+                    tool_calls.next().map(ResponsePart::ToolCall)
+                } else if let Some(data) = &part.inline_data {
+                    Some(ResponsePart::InlineData {
+                        mime_type: data.mime_type.clone(),
+                        data: data.data.clone(),
+                    })
+                } else if part.thought {
+                    part.text.clone().map(ResponsePart::Thought)
+                } else {
+                    part.text.clone().map(ResponsePart::Text)
+                }
+            })
+            .collect()
+    }
+
+    /// Convert all function call parts into tool calls.
+    ///
+    /// Gemini does not always assign ids to function calls, so calls without one get
+    /// an id derived from their position in the response.
+    pub fn tool_calls(&self) -> Vec<ToolCall> {
+        self.tool_calls_from(0)
+    }
+
+    /// Like [`tool_calls`](Self::tool_calls), for a response streamed in chunks:
+    /// `first_index` is the number of calls received in earlier chunks, so that
+    /// derived ids stay unique across the whole response.
+    pub fn tool_calls_from(&self, first_index: usize) -> Vec<ToolCall> {
+        self.parts
+            .iter()
+            .filter_map(|p| p.function_call.as_ref())
+            .zip(first_index..)
+            .map(|(call, index)| ToolCall {
+                id: call
+                    .id
+                    .clone()
+                    .unwrap_or_else(|| format!("{}_{}", call.name, index)),
+                call_id: call.id.clone(),
+                function: ToolFunction {
+                    name: call.name.clone(),
+                    arguments: call.args.clone(),
+                },
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UsageMetadata {
-    #[serde(rename = "promptTokenCount")]
+    #[serde(rename = "promptTokenCount", default)]
     pub prompt_token_count: u32,
-    #[serde(rename = "candidatesTokenCount")]
+    #[serde(rename = "candidatesTokenCount", default)]
     pub candidates_token_count: u32,
-    #[serde(rename = "totalTokenCount")]
+    #[serde(rename = "totalTokenCount", default)]
     pub total_token_count: u32,
+    #[serde(rename = "thoughtsTokenCount")]
+    pub thoughts_token_count: Option<u32>,
     #[serde(rename = "promptTokensDetails")]
     pub prompt_tokens_details: Option<Vec<PromptTokensDetail>>,
 }
@@ -146,3 +581,14 @@ pub struct PromptTokensDetail {
     #[serde(rename = "tokenCount")]
     pub token_count: u32,
 }
+
+impl From<&UsageMetadata> for Usage {
+    fn from(usage: &UsageMetadata) -> Self {
+        Usage {
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens: usage.candidates_token_count,
+            total_tokens: usage.total_token_count,
+            thoughts_tokens: usage.thoughts_token_count,
+        }
+    }
+}
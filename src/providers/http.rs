@@ -0,0 +1,214 @@
+//! The HTTP client owned by each provider, and turning failed responses into errors.
+
+use std::time::Duration;
+
+use reqwest::{Response, header::HeaderMap};
+
+use crate::{
+    error::{OrchestraError, Result},
+    providers::config::ProviderConfig,
+};
+
+/// A provider's HTTP client, built once from its configuration and shared by all of
+/// its requests.
+///
+/// [`Provider::new`](super::Provider::new) cannot fail, so an invalid configuration
+/// (e.g. a malformed proxy URL) is kept and reported by every request instead.
+#[derive(Debug, Clone)]
+pub(crate) struct HttpClient {
+    client: std::result::Result<reqwest::Client, String>,
+}
+
+impl HttpClient {
+    pub(crate) fn new(config: &ProviderConfig) -> Self {
+        Self {
+            client: config.build_http_client().map_err(|e| match e {
+                OrchestraError::Config { message } => message,
+                other => other.to_string(),
+            }),
+        }
+    }
+
+    /// The client, or the error that prevented building it
+    pub(crate) fn get(&self) -> Result<&reqwest::Client> {
+        self.client
+            .as_ref()
+            .map_err(|message| OrchestraError::config(message.clone()))
+    }
+}
+
+/// Turn an unsuccessful response into the [`OrchestraError`] variant matching its status.
+///
+/// `parse_message` extracts a readable message from the provider's error body; the raw
+/// body is used when it returns `None`. A `Retry-After` header is kept on rate limit
+/// errors.
+pub(crate) async fn error_from_response<F>(
+    provider: &str,
+    resp: Response,
+    parse_message: F,
+) -> OrchestraError
+where
+    F: FnOnce(&str) -> Option<String>,
+{
+    let status = resp.status().as_u16();
+    let delay = retry_after(resp.headers());
+    let body = resp.text().await.unwrap_or_default();
+    let message = parse_message(&body).unwrap_or_else(|| body.clone());
+
+    let mut error = OrchestraError::from_status(provider, status, message, Some(body));
+    if let OrchestraError::RateLimit { retry_after, .. } = &mut error {
+        *retry_after = delay;
+    }
+    error
+}
+
+/// Like [`error_from_response`], for requests that name a model: a `404` means the
+/// model does not exist and becomes an [`OrchestraError::Model`].
+pub(crate) async fn model_error_from_response<F>(
+    provider: &str,
+    resp: Response,
+    parse_message: F,
+) -> OrchestraError
+where
+    F: FnOnce(&str) -> Option<String>,
+{
+    error_from_response(provider, resp, parse_message)
+        .await
+        .into_model_error()
+}
+
+/// The delay requested by a `Retry-After` header in its delay-seconds form
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::test_server::{MockResponse, TestServer};
+
+    #[test]
+    fn test_invalid_proxy_is_reported_on_use() {
+        let client = HttpClient::new(&ProviderConfig::new().with_proxy("not a proxy url"));
+
+        match client.get() {
+            Err(OrchestraError::Config { message }) => {
+                assert!(message.starts_with("Invalid proxy not a proxy url"), "{}", message);
+            }
+            other => panic!("Expected config error, got {:?}", other),
+        }
+    }
+
+    fn error_message(body: &str) -> Option<String> {
+        serde_json::from_str::<serde_json::Value>(body)
+            .ok()?
+            .pointer("/error/message")?
+            .as_str()
+            .map(str::to_string)
+    }
+
+    #[tokio::test]
+    async fn test_error_from_response() {
+        let server = TestServer::start(vec![
+            MockResponse::status(429, serde_json::json!({"error": {"message": "Slow down"}}))
+                .with_header("Retry-After", "7"),
+            MockResponse::status(403, serde_json::json!({"error": {"message": "Forbidden"}})),
+            MockResponse::status(404, serde_json::json!({"error": {"message": "File not found"}})),
+            MockResponse::status(503, serde_json::json!({"unexpected": true})),
+        ])
+        .await;
+        let client = reqwest::Client::new();
+
+        let mut errors = Vec::new();
+        for _ in 0..4 {
+            let resp = client.get(server.url()).send().await.unwrap();
+            errors.push(error_from_response("test", resp, error_message).await);
+        }
+
+        match &errors[0] {
+            OrchestraError::RateLimit {
+                message,
+                retry_after,
+                status,
+                ..
+            } => {
+                assert_eq!(message, "Slow down");
+                assert_eq!(*retry_after, Some(Duration::from_secs(7)));
+                assert_eq!(*status, Some(429));
+            }
+            other => panic!("Expected rate limit error, got {:?}", other),
+        }
+        assert!(matches!(
+            &errors[1],
+            OrchestraError::Authentication { status: Some(403), .. }
+        ));
+        // Without a model in the request, a 404 is not a model error
+        assert!(matches!(
+            &errors[2],
+            OrchestraError::Provider { status: Some(404), .. }
+        ));
+        match &errors[3] {
+            OrchestraError::Provider { message, status, body, .. } => {
+                assert_eq!(message, "{\"unexpected\":true}");
+                assert_eq!(*status, Some(503));
+                assert_eq!(body.as_deref(), Some("{\"unexpected\":true}"));
+            }
+            other => panic!("Expected provider error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout_is_applied() {
+        // A server that accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                sockets.push(socket);
+            }
+        });
+
+        let client = HttpClient::new(&ProviderConfig::new().with_timeout(1));
+        let result = tokio::time::timeout(
+            Duration::from_secs(10),
+            client.get().unwrap().get(url).send(),
+        )
+        .await
+        .expect("the read timeout should end the request");
+
+        assert!(matches!(
+            result.map_err(OrchestraError::from),
+            Err(OrchestraError::Timeout { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_user_agent() {
+        let server = TestServer::start(vec![
+            MockResponse::json(serde_json::json!({})),
+            MockResponse::json(serde_json::json!({})),
+        ])
+        .await;
+
+        let default_client = HttpClient::new(&ProviderConfig::new());
+        default_client.get().unwrap().get(server.url()).send().await.unwrap();
+
+        let custom_client = HttpClient::new(&ProviderConfig::new().with_user_agent("my-app/1.0"));
+        custom_client.get().unwrap().get(server.url()).send().await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].header("user-agent"),
+            Some(concat!("orchestra-rs/", env!("CARGO_PKG_VERSION")))
+        );
+        assert_eq!(requests[1].header("user-agent"), Some("my-app/1.0"));
+    }
+}
@@ -3,36 +3,57 @@ use crate::{
     error::Result,
     messages::Message,
     model::ModelConfig,
-    providers::{Provider, types::ChatResponse},
+    providers::{
+        Provider,
+        types::{ChatResponse, ChatStream, ChatStreamEvent, FinishReason},
+    },
 };
 
+/// A request received by the mock provider
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub model_config: ModelConfig,
+    pub message: Message,
+    pub chat_history: Vec<Message>,
+}
+
 /// Mock provider for testing purposes
 #[derive(Debug)]
 pub struct MockProvider {
     /// Predefined responses to return
-    pub responses: Vec<String>,
+    pub responses: Vec<ChatResponse>,
+    /// Requests received so far, shared so tests can inspect them after the
+    /// provider has been moved into an `LLM`
+    pub requests: std::sync::Arc<std::sync::Mutex<Vec<MockRequest>>>,
     /// Current response index
     pub current_index: std::sync::Arc<std::sync::Mutex<usize>>,
     /// Whether to simulate errors
     pub should_error: bool,
     /// Delay to simulate network latency (in milliseconds)
     pub delay_ms: Option<u64>,
+    /// Whether to report tool support
+    pub tool_support: bool,
 }
 
 /// Configuration for the mock provider
 #[derive(Debug, Clone)]
 pub struct MockConfig {
-    pub responses: Vec<String>,
+    pub responses: Vec<ChatResponse>,
     pub should_error: bool,
     pub delay_ms: Option<u64>,
+    pub tool_support: bool,
 }
 
 impl Default for MockConfig {
     fn default() -> Self {
         Self {
-            responses: vec!["Mock response".to_string()],
+            responses: vec![ChatResponse {
+                text: "Mock response".to_string(),
+                ..Default::default()
+            }],
             should_error: false,
             delay_ms: None,
+            tool_support: true,
         }
     }
 }
@@ -47,7 +68,22 @@ impl MockConfig {
         I: IntoIterator<Item = S>,
         S: Into<String>,
     {
-        self.responses = responses.into_iter().map(|s| s.into()).collect();
+        self.responses = responses
+            .into_iter()
+            .map(|s| ChatResponse {
+                text: s.into(),
+                ..Default::default()
+            })
+            .collect();
+        self
+    }
+
+    /// Set full responses, e.g. ones carrying tool calls
+    pub fn with_chat_responses<I>(mut self, responses: I) -> Self
+    where
+        I: IntoIterator<Item = ChatResponse>,
+    {
+        self.responses = responses.into_iter().collect();
         self
     }
 
@@ -60,24 +96,33 @@ impl MockConfig {
         self.delay_ms = Some(delay_ms);
         self
     }
+
+    /// Simulate a provider that does or does not support tool calling
+    pub fn with_tool_support(mut self, tool_support: bool) -> Self {
+        self.tool_support = tool_support;
+        self
+    }
 }
 
 impl MockProvider {
     pub fn new(config: MockConfig) -> Self {
         Self {
             responses: config.responses,
+            requests: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
             current_index: std::sync::Arc::new(std::sync::Mutex::new(0)),
             should_error: config.should_error,
             delay_ms: config.delay_ms,
+            tool_support: config.tool_support,
         }
     }
 
     /// Get the next response from the predefined list
-    fn get_next_response(&self) -> String {
+    fn get_next_response(&self) -> ChatResponse {
         let mut index = self.current_index.lock().unwrap();
-        let response = self.responses.get(*index)
-            .unwrap_or(&"Default mock response".to_string())
-            .clone();
+        let response = self.responses.get(*index).cloned().unwrap_or_else(|| ChatResponse {
+            text: "Default mock response".to_string(),
+            ..Default::default()
+        });
         *index = (*index + 1) % self.responses.len();
         response
     }
@@ -111,10 +156,16 @@ impl Provider for MockProvider {
 
     async fn chat(
         &self,
-        _model_config: ModelConfig,
-        _message: Message,
-        _chat_history: Vec<Message>,
+        model_config: ModelConfig,
+        message: Message,
+        chat_history: Vec<Message>,
     ) -> Result<ChatResponse> {
+        self.requests.lock().unwrap().push(MockRequest {
+            model_config,
+            message,
+            chat_history,
+        });
+
         if self.should_error {
             return Err(crate::error::OrchestraError::provider(
                 "mock",
@@ -127,9 +178,7 @@ impl Provider for MockProvider {
             tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
         }
 
-        Ok(ChatResponse {
-            text: self.get_next_response(),
-        })
+        Ok(self.get_next_response())
     }
 
     async fn prompt(
@@ -140,6 +189,25 @@ impl Provider for MockProvider {
         self.chat(model_config, Message::human(prompt), vec![]).await
     }
 
+    async fn chat_stream(
+        &self,
+        model_config: ModelConfig,
+        message: Message,
+        chat_history: Vec<Message>,
+    ) -> Result<ChatStream> {
+        let response = self.chat(model_config, message, chat_history).await?;
+
+        // Emit the response word by word to simulate incremental delivery
+        let mut events: Vec<Result<ChatStreamEvent>> = response
+            .text
+            .split_inclusive(' ')
+            .map(|chunk| Ok(ChatStreamEvent::TextDelta(chunk.to_string())))
+            .collect();
+        events.push(Ok(ChatStreamEvent::Finish(FinishReason::Stop)));
+
+        Ok(Box::pin(futures::stream::iter(events)))
+    }
+
     fn name(&self) -> &'static str {
         "mock"
     }
@@ -149,7 +217,7 @@ impl Provider for MockProvider {
     }
 
     fn supports_tools(&self) -> bool {
-        true // Mock provider can simulate tool support
+        self.tool_support
     }
 }
 
@@ -206,6 +274,33 @@ mod tests {
         assert!(duration.as_millis() >= 100);
     }
 
+    #[tokio::test]
+    async fn test_mock_provider_stream() {
+        use futures::StreamExt;
+
+        let config = MockConfig::new().with_responses(vec!["Hello streaming world"]);
+        let provider = MockProvider::new(config);
+
+        let model_config = ModelConfig::new("mock-model-1");
+        let events: Vec<ChatStreamEvent> = provider
+            .prompt_stream(model_config, "test".to_string())
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(
+            events,
+            vec![
+                ChatStreamEvent::TextDelta("Hello ".to_string()),
+                ChatStreamEvent::TextDelta("streaming ".to_string()),
+                ChatStreamEvent::TextDelta("world".to_string()),
+                ChatStreamEvent::Finish(FinishReason::Stop),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_mock_provider_reset() {
         let config = MockConfig::new()
@@ -1,7 +1,15 @@
+pub mod anthropic;
 pub mod config;
 pub mod gemini;
+pub(crate) mod http;
 #[cfg(test)]
 pub mod mock;
+pub mod ollama;
+pub mod openai;
+pub mod retry;
+pub mod sse;
+#[cfg(test)]
+pub mod test_server;
 pub mod types;
 
 use async_trait::async_trait;
@@ -10,7 +18,7 @@ use crate::{
     error::Result,
     messages::Message,
     model::ModelConfig,
-    providers::types::ChatResponse
+    providers::types::{ChatResponse, ChatStream, ChatStreamEvent, ResponsePart},
 };
 
 /// A trait for all providers to implement.
@@ -28,6 +36,14 @@ pub trait Provider: Send + Sync + std::fmt::Debug {
     /// Get a list of all predefined models for this provider.
     fn get_predefined_models(&self) -> Result<Vec<String>>;
 
+    /// Get the models available from this provider.
+    ///
+    /// Defaults to `get_predefined_models`. Providers whose models can only be
+    /// discovered from the server, like Ollama, override this to query it.
+    async fn list_models(&self) -> Result<Vec<String>> {
+        self.get_predefined_models()
+    }
+
     /// Sends a chat request to the provider.
     async fn chat(
         &self,
@@ -44,6 +60,46 @@ pub trait Provider: Send + Sync + std::fmt::Debug {
         prompt: String,
     ) -> Result<ChatResponse>;
 
+    /// Sends a chat request and returns a stream of incremental response events.
+    ///
+    /// The default implementation waits for the full `chat` response and emits it
+    /// as a single [`ChatStreamEvent::TextDelta`], followed by its inline data, tool
+    /// calls, finish reason and usage. Providers that return `true` from `supports_streaming`
+    /// should override this with a real streaming request.
+    async fn chat_stream(
+        &self,
+        model_config: ModelConfig,
+        message: Message,
+        chat_history: Vec<Message>,
+    ) -> Result<ChatStream> {
+        let response = self.chat(model_config, message, chat_history).await?;
+
+        let events: Vec<Result<ChatStreamEvent>> = response
+            .thought_summary()
+            .map(ChatStreamEvent::ThoughtDelta)
+            .into_iter()
+            .chain(std::iter::once(ChatStreamEvent::TextDelta(response.text)))
+            .chain(response.parts.into_iter().filter_map(|part| match part {
+                ResponsePart::InlineData { mime_type, data } => {
+                    Some(ChatStreamEvent::InlineData { mime_type, data })
+                }
+                _ => None,
+            }))
+            .chain(response.tool_calls.into_iter().map(ChatStreamEvent::ToolCall))
+            .chain(response.finish_reason.map(ChatStreamEvent::Finish))
+            .chain(response.usage.map(ChatStreamEvent::Usage))
+            .map(Ok)
+            .collect();
+        Ok(Box::pin(futures::stream::iter(events)))
+    }
+
+    /// Sends a prompt request and returns a stream of incremental response events.
+    /// Internally this just calls `chat_stream` with a single message.
+    async fn prompt_stream(&self, model_config: ModelConfig, prompt: String) -> Result<ChatStream> {
+        self.chat_stream(model_config, Message::human(prompt), vec![])
+            .await
+    }
+
     /// Get the provider's name
     fn name(&self) -> &'static str;
 
@@ -95,10 +151,21 @@ pub trait ProviderExt: Send + Sync + std::fmt::Debug {
 
     async fn prompt(&self, model_config: ModelConfig, prompt: String) -> Result<ChatResponse>;
 
+    async fn chat_stream(
+        &self,
+        model_config: ModelConfig,
+        message: Message,
+        chat_history: Vec<Message>,
+    ) -> Result<ChatStream>;
+
+    async fn prompt_stream(&self, model_config: ModelConfig, prompt: String) -> Result<ChatStream>;
+
     fn get_base_url(&self) -> &str;
 
     fn get_predefined_models(&self) -> Result<Vec<String>>;
 
+    async fn list_models(&self) -> Result<Vec<String>>;
+
     fn name(&self) -> &'static str;
 
     /// Returns whether the provider supports streaming responses.
@@ -198,6 +265,29 @@ where
         Provider::prompt(self, model_config, prompt).await
     }
 
+    /// Forwards a streaming chat request to the underlying `Provider::chat_stream`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// // Inside an async context:
+    /// // let mut stream = provider.chat_stream(model_config, user_message, chat_history).await?;
+    /// // while let Some(event) = stream.next().await { /* ... */ }
+    /// ```
+    async fn chat_stream(
+        &self,
+        model_config: ModelConfig,
+        message: Message,
+        chat_history: Vec<Message>,
+    ) -> Result<ChatStream> {
+        Provider::chat_stream(self, model_config, message, chat_history).await
+    }
+
+    /// Forwards a streaming prompt request to the underlying `Provider::prompt_stream`.
+    async fn prompt_stream(&self, model_config: ModelConfig, prompt: String) -> Result<ChatStream> {
+        Provider::prompt_stream(self, model_config, prompt).await
+    }
+
     /// Returns the provider's base URL used for requests.
     ///
     /// This method delegates to the underlying provider's `get_base_url` implementation.
@@ -225,6 +315,14 @@ where
         Provider::get_predefined_models(self)
     }
 
+    /// Returns the models available from this provider, querying the server where
+    /// the provider needs to.
+    ///
+    /// This method delegates to the underlying provider's `list_models` implementation.
+    async fn list_models(&self) -> Result<Vec<String>> {
+        Provider::list_models(self).await
+    }
+
     /// Returns the provider's static name.
     ///
     /// This delegates to the underlying `Provider::name` implementation and yields
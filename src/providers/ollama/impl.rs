@@ -0,0 +1,400 @@
+use crate::{
+    error::{OrchestraError, Result},
+    messages::Message,
+    model::ModelConfig,
+    providers::{
+        Provider,
+        config::OllamaConfig,
+        http::{HttpClient, error_from_response, model_error_from_response},
+        retry::RetryPolicy,
+        types::{Candidate, ChatResponse, ResponsePart},
+    },
+};
+
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName};
+
+use super::types::{OllamaChatResponse, OllamaErrorResponse, OllamaRequestBody, OllamaTagsResponse};
+
+#[derive(Debug)]
+pub struct OllamaProvider {
+    config: OllamaConfig,
+    base_url: String,
+    http: HttpClient,
+    /// Models returned by the last `list_local_models` call, empty until it is made
+    local_models: RwLock<Vec<String>>,
+}
+
+impl OllamaProvider {
+    /// Create a new OllamaProvider with default configuration
+    pub fn with_default_config() -> Self {
+        Self::new(OllamaConfig::default())
+    }
+
+    /// Build the headers sent with every request.
+    ///
+    /// Ollama itself needs no authentication, but servers behind a reverse proxy
+    /// often do, so a configured API key is sent as a bearer token along with any
+    /// custom headers.
+    fn build_headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+
+        if let Some(api_key) = &self.config.base.api_key {
+            headers.insert("Authorization", format!("Bearer {}", api_key).parse()?);
+        }
+        for (key, value) in &self.config.base.headers {
+            let name = HeaderName::from_bytes(key.as_bytes())
+                .map_err(|e| OrchestraError::config(format!("Invalid header name {}: {}", key, e)))?;
+            headers.insert(name, value.parse()?);
+        }
+
+        Ok(headers)
+    }
+
+    /// Fetch the names of the models available on the Ollama server from `/api/tags`.
+    ///
+    /// The result is also remembered and returned by `get_predefined_models`.
+    pub async fn list_local_models(&self) -> Result<Vec<String>> {
+        let resp = self
+            .http
+            .get()?
+            .get(format!("{}/api/tags", self.get_base_url()))
+            .headers(self.build_headers()?)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(Self::http_error(resp).await);
+        }
+
+        let tags: OllamaTagsResponse = resp.json().await?;
+        let models: Vec<String> = tags.models.into_iter().map(|m| m.name).collect();
+
+        if let Ok(mut local_models) = self.local_models.write() {
+            *local_models = models.clone();
+        }
+
+        Ok(models)
+    }
+
+    /// Turn an unsuccessful HTTP response into an error
+    async fn http_error(resp: reqwest::Response) -> OrchestraError {
+        error_from_response("ollama", resp, Self::error_message).await
+    }
+
+    /// The message of an Ollama error body
+    fn error_message(body: &str) -> Option<String> {
+        serde_json::from_str::<OllamaErrorResponse>(body)
+            .ok()
+            .map(|e| e.error)
+    }
+}
+
+#[async_trait]
+impl Provider for OllamaProvider {
+    type Config = OllamaConfig;
+
+    fn new(config: Self::Config) -> Self {
+        let base_url = config.get_base_url().trim_end_matches('/').to_string();
+        let http = HttpClient::new(&config.base);
+        Self {
+            config,
+            base_url,
+            http,
+            local_models: RwLock::new(Vec::new()),
+        }
+    }
+
+    fn get_base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    /// The models found by the last [`OllamaProvider::list_local_models`] call.
+    ///
+    /// This trait method is synchronous and must not block, so it never queries the
+    /// server itself and returns an empty list until the models have been listed;
+    /// call `list_models` from async code to fetch them.
+    fn get_predefined_models(&self) -> Result<Vec<String>> {
+        Ok(self
+            .local_models
+            .read()
+            .map(|models| models.clone())
+            .unwrap_or_default())
+    }
+
+    /// Fetch the models installed on the Ollama server from `/api/tags`
+    async fn list_models(&self) -> Result<Vec<String>> {
+        self.list_local_models().await
+    }
+
+    async fn prompt(&self, model_config: ModelConfig, prompt: String) -> Result<ChatResponse> {
+        self.chat(model_config, Message::human(prompt), vec![])
+            .await
+    }
+
+    async fn chat(
+        &self,
+        model_config: ModelConfig,
+        message: Message,
+        chat_history: Vec<Message>,
+    ) -> Result<ChatResponse> {
+        let client = self.http.get()?;
+
+        // Combine history + new_message
+        let mut messages_to_send = chat_history;
+        messages_to_send.push(message);
+
+        let request_url = format!("{}/api/chat", self.get_base_url());
+
+        let request_body = OllamaRequestBody::from_model_config(&model_config, &messages_to_send)?;
+
+        let request = client
+            .post(request_url)
+            .headers(self.build_headers()?)
+            .json(&request_body);
+        let resp = RetryPolicy::from_config(&self.config.base)
+            .send(request, |resp| {
+                model_error_from_response("ollama", resp, Self::error_message)
+            })
+            .await?;
+
+        let ollama_response: OllamaChatResponse = resp.json().await?;
+
+        if let Some(error) = ollama_response.error {
+            return Err(OrchestraError::provider("ollama", &error));
+        }
+
+        let finish_reason = ollama_response.finish_reason();
+        let usage = ollama_response.usage();
+
+        let message = ollama_response
+            .message
+            .ok_or_else(|| OrchestraError::invalid_response("No message in response"))?;
+
+        // Ollama always generates a single response
+        let candidate = Candidate {
+            finish_reason,
+            ..Candidate::from_parts(0, vec![ResponsePart::Text(message.content)])
+        };
+
+        Ok(ChatResponse {
+            usage,
+            model_version: ollama_response.model,
+            // Ollama does not assign ids to responses
+            response_id: None,
+            ..ChatResponse::from_candidates(vec![candidate])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        messages::ContentPart,
+        providers::{
+            config::ProviderConfig,
+            test_server::{
+                MockResponse, TestServer,
+                fixtures::{ollama_chat, ollama_tags},
+            },
+            types::{FinishReason, Usage},
+        },
+    };
+
+    #[tokio::test]
+    async fn test_chat_request_mapping() {
+        let server =
+            TestServer::start(vec![MockResponse::json(ollama_chat("Hello from llama!", "stop"))]).await;
+        let mut config = OllamaConfig::new().with_base_url(server.url());
+        config.base = config.base.with_header("X-Proxy-Token", "secret");
+        let provider = OllamaProvider::new(config);
+
+        let model_config = ModelConfig::new("llama3.2")
+            .with_system_instruction("You are friendly.")
+            .with_top_k(40)
+            .with_max_tokens(128);
+
+        let resp = provider
+            .chat(model_config, Message::human("Hi"), vec![])
+            .await
+            .unwrap();
+
+        assert_eq!(resp.text, "Hello from llama!");
+        assert_eq!(resp.finish_reason, Some(FinishReason::Stop));
+        assert_eq!(resp.usage.map(|u| u.total_tokens), Some(31));
+        assert_eq!(resp.model_version.as_deref(), Some("llama3.2"));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/api/chat");
+        assert!(requests[0].header("authorization").is_none());
+        assert_eq!(requests[0].header("x-proxy-token"), Some("secret"));
+
+        let body = requests[0].json();
+        assert_eq!(body["model"], "llama3.2");
+        assert_eq!(body["stream"], false);
+        assert_eq!(body["options"]["top_k"], 40);
+        assert_eq!(body["options"]["num_predict"], 128);
+        assert_eq!(
+            body["messages"],
+            serde_json::json!([
+                {"role": "system", "content": "You are friendly."},
+                {"role": "user", "content": "Hi"}
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_model_not_found() {
+        let server = TestServer::start(vec![MockResponse::status(
+            404,
+            serde_json::json!({"error": "model \"missing\" not found, try pulling it first"}),
+        )])
+        .await;
+        let provider = OllamaProvider::new(OllamaConfig::new().with_base_url(server.url()));
+
+        let result = provider
+            .prompt(ModelConfig::new("missing"), "Hi".to_string())
+            .await;
+
+        match result {
+            Err(OrchestraError::Model {
+                message,
+                provider,
+                status,
+                ..
+            }) => {
+                assert!(message.contains("try pulling it first"));
+                assert_eq!(provider.as_deref(), Some("ollama"));
+                assert_eq!(status, Some(404));
+            }
+            other => panic!("Expected model error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_retries_rate_limit() {
+        let server = TestServer::start(vec![
+            MockResponse::status(429, serde_json::json!({"error": "busy"})).with_header("Retry-After", "0"),
+            MockResponse::json(ollama_chat("Hi!", "stop")),
+        ])
+        .await;
+        let provider = OllamaProvider::new(OllamaConfig::new().with_base_url(server.url()));
+
+        let resp = provider
+            .prompt(ModelConfig::new("llama3.2"), "Hello".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(resp.text, "Hi!");
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_local_models() {
+        let server = TestServer::start(vec![MockResponse::json(ollama_tags())]).await;
+        let provider = OllamaProvider::new(OllamaConfig::new().with_base_url(server.url()));
+
+        let models = provider.list_local_models().await.unwrap();
+
+        assert_eq!(models, vec!["llama3.2:latest", "qwen2.5-coder:7b"]);
+        assert_eq!(server.requests()[0].path, "/api/tags");
+    }
+
+    #[tokio::test]
+    async fn test_list_models_through_trait_object() {
+        let server = TestServer::start(vec![MockResponse::json(ollama_tags())]).await;
+        let provider: Box<dyn crate::providers::ProviderExt> =
+            Box::new(OllamaProvider::new(OllamaConfig::new().with_base_url(server.url())));
+
+        let models = provider.list_models().await.unwrap();
+
+        assert_eq!(models, vec!["llama3.2:latest", "qwen2.5-coder:7b"]);
+        assert_eq!(server.requests()[0].path, "/api/tags");
+    }
+
+    #[test]
+    fn test_image_message_mapping() {
+        let config = ModelConfig::new("llava");
+        let message = Message::human_with_parts(vec![
+            ContentPart::text("What is this?"),
+            ContentPart::inline_data("image/jpeg", b"jpg"),
+        ]);
+
+        let body = OllamaRequestBody::from_model_config(&config, &[message]).unwrap();
+        assert_eq!(body.messages[0].content, "What is this?");
+        assert_eq!(body.messages[0].images, vec!["anBn".to_string()]);
+
+        let message = Message::human_with_parts(vec![ContentPart::inline_data("audio/wav", b"RIFF")]);
+        assert!(matches!(
+            OllamaRequestBody::from_model_config(&config, &[message]),
+            Err(OrchestraError::InvalidRequest { .. })
+        ));
+    }
+
+    #[test]
+    fn test_no_default_timeout() {
+        assert_eq!(OllamaConfig::new().base.get_read_timeout(), None);
+        assert_eq!(OllamaConfig::new().base.get_timeout(), u64::MAX);
+        assert_eq!(OllamaConfig::new().with_timeout(600).base.get_read_timeout(), Some(600));
+        assert_eq!(
+            ProviderConfig::new().get_timeout(),
+            ProviderConfig::DEFAULT_TIMEOUT_SECONDS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_predefined_models_after_listing() {
+        let server = TestServer::start(vec![MockResponse::json(ollama_tags())]).await;
+        let provider = OllamaProvider::new(OllamaConfig::new().with_base_url(server.url()));
+
+        // The synchronous getter never queries the server itself
+        assert!(provider.get_predefined_models().unwrap().is_empty());
+        assert!(server.requests().is_empty());
+
+        provider.list_local_models().await.unwrap();
+
+        assert_eq!(
+            provider.get_predefined_models().unwrap(),
+            vec!["llama3.2:latest", "qwen2.5-coder:7b"]
+        );
+    }
+
+    #[test]
+    fn test_response_parsing() {
+        let response: OllamaChatResponse =
+            serde_json::from_value(ollama_chat("...", "load")).unwrap();
+        assert_eq!(response.finish_reason(), Some(FinishReason::Other("load".to_string())));
+
+        // Ollama omits `prompt_eval_count` when the prompt was cached
+        let response: OllamaChatResponse = serde_json::from_value(serde_json::json!({
+            "message": {"role": "assistant", "content": "..."},
+            "done": true,
+            "eval_count": 32
+        }))
+        .unwrap();
+        assert_eq!(response.finish_reason(), None);
+        assert_eq!(
+            response.usage(),
+            Some(Usage {
+                prompt_tokens: 0,
+                completion_tokens: 32,
+                total_tokens: 32,
+                thoughts_tokens: None,
+            })
+        );
+
+        let response: OllamaChatResponse = serde_json::from_value(serde_json::json!({
+            "message": {"role": "assistant", "content": "..."},
+            "done": true
+        }))
+        .unwrap();
+        assert_eq!(response.usage(), None);
+    }
+}
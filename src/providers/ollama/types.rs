@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{OrchestraError, Result},
+    messages::{ContentPart, Message},
+    model::ModelConfig,
+    providers::types::{FinishReason, Usage, unsupported_content},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaRequestBody {
+    pub model: String,
+    pub messages: Vec<OllamaMessage>,
+    pub stream: bool,
+    pub options: OllamaOptions,
+    /// `"json"` or a JSON schema the reply must conform to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<serde_json::Value>,
+}
+
+impl OllamaRequestBody {
+    /// Build a non-streaming request body from the model configuration and the
+    /// messages to send. The system instruction, if any, is sent as the first
+    /// `system` message. Inline images on user turns are sent in the message's
+    /// `images`; other files are rejected.
+    ///
+    /// Tool calling is not supported yet: `ModelConfig::tools` is not sent, and tool
+    /// results in the history are sent as the text of a `tool` message while the
+    /// assistant turns that requested them carry no `tool_calls`.
+    pub fn from_model_config(config: &ModelConfig, messages: &[Message]) -> Result<Self> {
+        let system = config
+            .system_instruction
+            .as_ref()
+            .map(|instruction| OllamaMessage {
+                role: "system".to_string(),
+                content: instruction.clone(),
+                images: Vec::new(),
+            });
+
+        Ok(Self {
+            model: config.name.clone(),
+            messages: system
+                .into_iter()
+                .map(Ok)
+                .chain(messages.iter().map(OllamaMessage::try_from))
+                .collect::<Result<_>>()?,
+            stream: false,
+            options: OllamaOptions {
+                temperature: Some(config.temperature),
+                top_p: Some(config.top_p),
+                top_k: config.top_k,
+                num_predict: config.max_tokens,
+                stop: if config.stop_sequences.is_empty() {
+                    None
+                } else {
+                    Some(config.stop_sequences.clone())
+                },
+            },
+            format: config.response_schema.clone().or_else(|| {
+                config
+                    .wants_json()
+                    .then(|| serde_json::Value::from("json"))
+            }),
+        })
+    }
+}
+
+/// Model parameters sent in the `options` object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaMessage {
+    pub role: String,
+    pub content: String,
+    /// Base64-encoded images, for multimodal models
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
+}
+
+impl TryFrom<&Message> for OllamaMessage {
+    type Error = OrchestraError;
+
+    fn try_from(msg: &Message) -> Result<Self> {
+        let mut images = Vec::new();
+        match msg {
+            Message::Human(h) => {
+                for part in h.content.media_parts() {
+                    match part {
+                        ContentPart::InlineData { mime_type, data } if mime_type.starts_with("image/") => {
+                            images.push(data.clone())
+                        }
+                        part => return Err(unsupported_content("ollama", part)),
+                    }
+                }
+            }
+            Message::Assistant(a) => {
+                if let Some(part) = a.content.media_parts().next() {
+                    return Err(unsupported_content("ollama", part));
+                }
+            }
+            Message::System(_) | Message::Tool(_) => {}
+        }
+
+        Ok(OllamaMessage {
+            role: msg.role().to_string(),
+            content: msg.content_text(),
+            images,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OllamaChatResponse {
+    pub model: Option<String>,
+    pub message: Option<OllamaMessage>,
+    #[serde(default)]
+    pub done: bool,
+    pub done_reason: Option<String>,
+    pub prompt_eval_count: Option<u32>,
+    pub eval_count: Option<u32>,
+    pub error: Option<String>,
+}
+
+impl OllamaChatResponse {
+    /// Get the normalized finish reason, if the response has one
+    pub fn finish_reason(&self) -> Option<FinishReason> {
+        self.done_reason.as_deref().map(|reason| match reason {
+            "stop" => FinishReason::Stop,
+            "length" => FinishReason::MaxTokens,
+            other => FinishReason::Other(other.to_string()),
+        })
+    }
+
+    /// Get the token usage, if the response reports it
+    pub fn usage(&self) -> Option<Usage> {
+        if self.prompt_eval_count.is_none() && self.eval_count.is_none() {
+            return None;
+        }
+
+        let prompt_tokens = self.prompt_eval_count.unwrap_or(0);
+        let completion_tokens = self.eval_count.unwrap_or(0);
+        Some(Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            thoughts_tokens: None,
+        })
+    }
+}
+
+/// Response of `GET /api/tags`, listing the locally available models
+#[derive(Debug, Deserialize)]
+pub struct OllamaTagsResponse {
+    #[serde(default)]
+    pub models: Vec<OllamaModel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    pub model: Option<String>,
+    pub size: Option<u64>,
+}
+
+/// Error body returned by Ollama, e.g. `{"error": "model 'x' not found"}`
+#[derive(Debug, Deserialize)]
+pub struct OllamaErrorResponse {
+    pub error: String,
+}
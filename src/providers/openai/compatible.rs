@@ -0,0 +1,281 @@
+use crate::{
+    error::{OrchestraError, Result},
+    messages::Message,
+    model::ModelConfig,
+    providers::{
+        Provider, config::ProviderConfig, http::HttpClient, retry::RetryPolicy, types::ChatResponse,
+    },
+};
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName};
+
+use super::r#impl::chat_completion;
+
+/// Provider for any server that speaks the OpenAI Chat Completions wire format,
+/// such as vLLM, llama.cpp server, LM Studio, LocalAI, Groq or Together.
+///
+/// Everything vendor specific comes from the [`ProviderConfig`]:
+///
+/// - `base_url` (required): the API root, e.g. `http://localhost:8000/v1`
+/// - `api_key`: optional, sent in the auth header when set
+/// - `headers`: extra headers added to every request
+/// - `custom["auth_header"]`: name of the header carrying the API key
+///   (defaults to `Authorization`)
+/// - `custom["auth_scheme"]`: prefix for the key, e.g. `Bearer`. Defaults to
+///   `Bearer` for the `Authorization` header and to no prefix otherwise
+/// - `custom["api_key_env"]`: environment variable to read the key from when
+///   `api_key` is not set
+/// - `custom["models"]`: array of model names returned by `get_predefined_models`
+///
+/// # Examples
+///
+/// ```
+/// use orchestra_rs::providers::{
+///     Provider, config::ProviderConfig, openai::OpenAICompatibleProvider,
+/// };
+///
+/// let config = ProviderConfig::new()
+///     .with_base_url("http://localhost:8000/v1")
+///     .with_custom("models", serde_json::json!(["meta-llama/Llama-3.1-8B-Instruct"]));
+///
+/// let provider = OpenAICompatibleProvider::new(config);
+/// assert_eq!(provider.get_base_url(), "http://localhost:8000/v1");
+/// ```
+#[derive(Debug)]
+pub struct OpenAICompatibleProvider {
+    config: ProviderConfig,
+    base_url: String,
+    http: HttpClient,
+}
+
+impl OpenAICompatibleProvider {
+    /// Custom configuration key for the name of the auth header
+    pub const AUTH_HEADER_KEY: &str = "auth_header";
+    /// Custom configuration key for the auth scheme prefixed to the API key
+    pub const AUTH_SCHEME_KEY: &str = "auth_scheme";
+    /// Custom configuration key for the API key environment variable
+    pub const API_KEY_ENV_KEY: &str = "api_key_env";
+    /// Custom configuration key for the list of available models
+    pub const MODELS_KEY: &str = "models";
+
+    /// Get the API key from configuration or the configured environment variable
+    fn get_api_key(&self) -> Option<String> {
+        match self
+            .config
+            .get_custom(Self::API_KEY_ENV_KEY)
+            .and_then(|v| v.as_str())
+        {
+            Some(env_var) => self.config.get_api_key(env_var),
+            None => self.config.api_key.clone(),
+        }
+    }
+
+    /// Build the headers shared by all requests
+    fn build_headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+
+        headers.insert("Content-Type", "application/json".parse()?);
+
+        if let Some(api_key) = self.get_api_key() {
+            let header_name = self
+                .config
+                .get_custom(Self::AUTH_HEADER_KEY)
+                .and_then(|v| v.as_str())
+                .unwrap_or("Authorization");
+
+            let default_scheme = if header_name.eq_ignore_ascii_case("authorization") {
+                Some("Bearer")
+            } else {
+                None
+            };
+            let scheme = self
+                .config
+                .get_custom(Self::AUTH_SCHEME_KEY)
+                .and_then(|v| v.as_str())
+                .or(default_scheme)
+                .filter(|s| !s.is_empty());
+
+            let value = match scheme {
+                Some(scheme) => format!("{} {}", scheme, api_key),
+                None => api_key,
+            };
+            headers.insert(parse_header_name(header_name)?, value.parse()?);
+        }
+
+        for (key, value) in &self.config.headers {
+            headers.insert(parse_header_name(key)?, value.parse()?);
+        }
+
+        Ok(headers)
+    }
+}
+
+fn parse_header_name(name: &str) -> Result<HeaderName> {
+    HeaderName::from_bytes(name.as_bytes())
+        .map_err(|e| OrchestraError::config(format!("Invalid header name {}: {}", name, e)))
+}
+
+#[async_trait]
+impl Provider for OpenAICompatibleProvider {
+    type Config = ProviderConfig;
+
+    fn new(config: Self::Config) -> Self {
+        let base_url = config
+            .base_url
+            .clone()
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_string();
+        let http = HttpClient::new(&config);
+        Self {
+            config,
+            base_url,
+            http,
+        }
+    }
+
+    fn get_base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn name(&self) -> &'static str {
+        "openai_compatible"
+    }
+
+    fn get_predefined_models(&self) -> Result<Vec<String>> {
+        match self.config.get_custom(Self::MODELS_KEY) {
+            None => Ok(vec![]),
+            Some(models) => serde_json::from_value(models.clone()).map_err(|_| {
+                OrchestraError::config("custom \"models\" must be an array of model names")
+            }),
+        }
+    }
+
+    async fn prompt(&self, model_config: ModelConfig, prompt: String) -> Result<ChatResponse> {
+        self.chat(model_config, Message::human(prompt), vec![])
+            .await
+    }
+
+    async fn chat(
+        &self,
+        model_config: ModelConfig,
+        message: Message,
+        chat_history: Vec<Message>,
+    ) -> Result<ChatResponse> {
+        if self.base_url.is_empty() {
+            return Err(OrchestraError::config(
+                "base_url is required for OpenAI-compatible providers",
+            ));
+        }
+
+        let headers = self.build_headers()?;
+
+        // Combine history + new_message
+        let mut messages_to_send = chat_history;
+        messages_to_send.push(message);
+
+        chat_completion(
+            self.http.get()?,
+            &RetryPolicy::from_config(&self.config),
+            self.name(),
+            self.get_base_url(),
+            headers,
+            &model_config,
+            &messages_to_send,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::test_server::{MockResponse, TestServer, fixtures::openai_completion};
+
+    #[tokio::test]
+    async fn test_custom_auth_header_and_headers() {
+        let server =
+            TestServer::start(vec![MockResponse::json(openai_completion("Hi from vLLM", "stop"))]).await;
+        let config = ProviderConfig::new()
+            .with_base_url(format!("{}/v1/", server.url()))
+            .with_api_key("secret")
+            .with_header("X-Tenant", "team-a")
+            .with_custom(OpenAICompatibleProvider::AUTH_HEADER_KEY, "api-key");
+        let provider = OpenAICompatibleProvider::new(config);
+
+        let resp = provider
+            .prompt(ModelConfig::new("local-model"), "Hello".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(resp.text, "Hi from vLLM");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/chat/completions");
+        assert_eq!(requests[0].header("api-key"), Some("secret"));
+        assert!(requests[0].header("authorization").is_none());
+        assert_eq!(requests[0].header("x-tenant"), Some("team-a"));
+        assert_eq!(requests[0].json()["model"], "local-model");
+    }
+
+    #[tokio::test]
+    async fn test_default_bearer_auth_and_no_key() {
+        let server = TestServer::start(vec![
+            MockResponse::json(openai_completion("one", "stop")),
+            MockResponse::json(openai_completion("two", "stop")),
+        ])
+        .await;
+
+        let with_key = OpenAICompatibleProvider::new(
+            ProviderConfig::new()
+                .with_base_url(server.url())
+                .with_api_key("gsk_123"),
+        );
+        with_key
+            .prompt(ModelConfig::new("m"), "Hello".to_string())
+            .await
+            .unwrap();
+
+        let without_key =
+            OpenAICompatibleProvider::new(ProviderConfig::new().with_base_url(server.url()));
+        without_key
+            .prompt(ModelConfig::new("m"), "Hello".to_string())
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("authorization"), Some("Bearer gsk_123"));
+        assert!(requests[1].header("authorization").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_missing_base_url() {
+        let provider = OpenAICompatibleProvider::new(ProviderConfig::new());
+
+        let result = provider
+            .prompt(ModelConfig::new("m"), "Hello".to_string())
+            .await;
+
+        assert!(matches!(result, Err(OrchestraError::Config { .. })));
+    }
+
+    #[test]
+    fn test_models_from_custom_config() {
+        let provider = OpenAICompatibleProvider::new(
+            ProviderConfig::new()
+                .with_custom("models", serde_json::json!(["llama-3.1-8b", "mixtral-8x7b"])),
+        );
+        assert_eq!(
+            provider.get_predefined_models().unwrap(),
+            vec!["llama-3.1-8b", "mixtral-8x7b"]
+        );
+
+        let provider = OpenAICompatibleProvider::new(ProviderConfig::new());
+        assert!(provider.get_predefined_models().unwrap().is_empty());
+
+        let provider =
+            OpenAICompatibleProvider::new(ProviderConfig::new().with_custom("models", "llama"));
+        assert!(provider.get_predefined_models().is_err());
+    }
+}
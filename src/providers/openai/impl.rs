@@ -0,0 +1,604 @@
+use crate::{
+    error::{OrchestraError, Result},
+    messages::Message,
+    model::ModelConfig,
+    providers::{
+        Provider,
+        config::OpenAIConfig,
+        http::{HttpClient, model_error_from_response},
+        retry::RetryPolicy,
+        types::{Candidate, ChatResponse, ResponsePart, Usage},
+    },
+};
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName};
+
+use super::types::{OpenAIChatResponse, OpenAIErrorResponse, OpenAIRequestBody, PREDEFINED_MODELS};
+
+#[derive(Debug)]
+pub struct OpenAIProvider {
+    config: OpenAIConfig,
+    base_url: String,
+    http: HttpClient,
+}
+
+impl OpenAIProvider {
+    pub const DEFAULT_API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+    /// Create a new OpenAIProvider with default configuration
+    pub fn with_default_config() -> Self {
+        Self::new(OpenAIConfig::default())
+    }
+
+    /// Build the headers shared by all OpenAI requests
+    fn build_headers(&self) -> Result<HeaderMap> {
+        let api_key = self.config.get_api_key().ok_or_else(|| {
+            OrchestraError::api_key("API key not found in configuration or environment")
+        })?;
+
+        let mut headers = HeaderMap::new();
+
+        headers.insert("Authorization", format!("Bearer {}", api_key).parse()?);
+        headers.insert("Content-Type", "application/json".parse()?);
+        if let Some(organization) = &self.config.organization {
+            headers.insert("OpenAI-Organization", organization.parse()?);
+        }
+        for (key, value) in &self.config.base.headers {
+            let name = HeaderName::from_bytes(key.as_bytes())
+                .map_err(|e| OrchestraError::config(format!("Invalid header name {}: {}", key, e)))?;
+            headers.insert(name, value.parse()?);
+        }
+
+        Ok(headers)
+    }
+}
+
+#[async_trait]
+impl Provider for OpenAIProvider {
+    type Config = OpenAIConfig;
+
+    fn new(config: Self::Config) -> Self {
+        let base_url = config.get_base_url().trim_end_matches('/').to_string();
+        let http = HttpClient::new(&config.base);
+        Self {
+            config,
+            base_url,
+            http,
+        }
+    }
+
+    fn get_base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn get_predefined_models(&self) -> Result<Vec<String>> {
+        Ok(PREDEFINED_MODELS.iter().map(|s| s.to_string()).collect())
+    }
+
+    async fn prompt(&self, model_config: ModelConfig, prompt: String) -> Result<ChatResponse> {
+        self.chat(model_config, Message::human(prompt), vec![])
+            .await
+    }
+
+    async fn chat(
+        &self,
+        model_config: ModelConfig,
+        message: Message,
+        chat_history: Vec<Message>,
+    ) -> Result<ChatResponse> {
+        let headers = self.build_headers()?;
+
+        // Combine history + new_message
+        let mut messages_to_send = chat_history;
+        messages_to_send.push(message);
+
+        chat_completion(
+            self.http.get()?,
+            &RetryPolicy::from_config(&self.config.base),
+            self.name(),
+            self.get_base_url(),
+            headers,
+            &model_config,
+            &messages_to_send,
+        )
+        .await
+    }
+}
+
+/// Send a `/chat/completions` request, retrying transient failures, and extract the reply.
+///
+/// Shared by every provider speaking the OpenAI wire format; `provider` is only
+/// used to attribute errors.
+pub(crate) async fn chat_completion(
+    client: &reqwest::Client,
+    retry_policy: &RetryPolicy,
+    provider: &str,
+    base_url: &str,
+    headers: HeaderMap,
+    model_config: &ModelConfig,
+    messages: &[Message],
+) -> Result<ChatResponse> {
+    let request_url = format!("{}/chat/completions", base_url);
+
+    let request_body = OpenAIRequestBody::from_model_config(model_config, messages)?;
+
+    let request = client.post(request_url).headers(headers).json(&request_body);
+    let resp = retry_policy
+        .send(request, |resp| {
+            model_error_from_response(provider, resp, OpenAIErrorResponse::parse_message)
+        })
+        .await?;
+
+    let openai_response: OpenAIChatResponse = resp.json().await?;
+
+    let choice = openai_response
+        .choices
+        .first()
+        .ok_or_else(|| OrchestraError::invalid_response("No choices in response"))?;
+
+    if choice.message.text().is_none() && choice.message.tool_calls.is_empty() {
+        return Err(OrchestraError::invalid_response("No content in response message"));
+    }
+
+    let candidates = openai_response
+        .choices
+        .iter()
+        .map(|choice| {
+            let text = choice.message.text().unwrap_or_default();
+            let parts = (!text.is_empty())
+                .then(|| ResponsePart::Text(text.to_string()))
+                .into_iter()
+                .chain(
+                    choice
+                        .message
+                        .tool_calls
+                        .iter()
+                        .map(|call| ResponsePart::ToolCall(call.into())),
+                )
+                .collect();
+
+            Candidate {
+                finish_reason: choice.finish_reason(),
+                ..Candidate::from_parts(choice.index, parts)
+            }
+        })
+        .collect();
+
+    Ok(ChatResponse {
+        usage: openai_response.usage.as_ref().map(Usage::from),
+        model_version: openai_response.model.clone(),
+        response_id: openai_response.id.clone(),
+        ..ChatResponse::from_candidates(candidates)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        messages::{ContentPart, ToolCall, ToolFunction},
+        providers::{
+            test_server::{MockResponse, TestServer, fixtures::openai_completion},
+            types::FinishReason,
+        },
+        tools::ToolDefinition,
+    };
+
+    #[tokio::test]
+    async fn test_chat_request_mapping() {
+        let server = TestServer::start(vec![MockResponse::json(openai_completion("Paris.", "stop"))]).await;
+        let provider = OpenAIProvider::new(
+            OpenAIConfig::new()
+                .with_api_key("test-key")
+                .with_base_url(server.url())
+                .with_organization("org-1")
+                .with_header("x-egress-token", "secret"),
+        );
+
+        let model_config = ModelConfig::new("gpt-4o-mini")
+            .with_system_instruction("Answer briefly.")
+            .with_temperature(0.2)
+            .unwrap()
+            .with_top_p(0.5)
+            .unwrap()
+            .with_max_tokens(64)
+            .with_stop_sequence("END");
+
+        let history = vec![
+            Message::human("Hi"),
+            Message::assistant("Hello! How can I help?"),
+        ];
+
+        let resp = provider
+            .chat(model_config, Message::human("Capital of France?"), history)
+            .await
+            .unwrap();
+
+        assert_eq!(resp.text, "Paris.");
+        assert_eq!(resp.finish_reason, Some(FinishReason::Stop));
+        assert_eq!(
+            resp.usage,
+            Some(Usage {
+                prompt_tokens: 12,
+                completion_tokens: 5,
+                total_tokens: 17,
+                thoughts_tokens: None
+            })
+        );
+        assert_eq!(resp.model_version.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(resp.response_id.as_deref(), Some("chatcmpl-123"));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/chat/completions");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer test-key"));
+        assert_eq!(requests[0].header("openai-organization"), Some("org-1"));
+        assert_eq!(requests[0].header("x-egress-token"), Some("secret"));
+
+        let body = requests[0].json();
+        assert_eq!(body["model"], "gpt-4o-mini");
+        assert_eq!(body["temperature"], 0.2);
+        assert_eq!(body["top_p"], 0.5);
+        assert_eq!(body["max_tokens"], 64);
+        assert_eq!(body["stop"], serde_json::json!(["END"]));
+        assert_eq!(
+            body["messages"],
+            serde_json::json!([
+                {"role": "system", "content": "Answer briefly."},
+                {"role": "user", "content": "Hi"},
+                {"role": "assistant", "content": "Hello! How can I help?"},
+                {"role": "user", "content": "Capital of France?"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_error_message_parsing() {
+        let body = serde_json::json!({"error": {
+            "message": "Incorrect API key provided",
+            "type": "invalid_request_error",
+            "code": "invalid_api_key"
+        }});
+        assert_eq!(
+            OpenAIErrorResponse::parse_message(&body.to_string()).as_deref(),
+            Some("Incorrect API key provided")
+        );
+        assert_eq!(OpenAIErrorResponse::parse_message("Bad Gateway"), None);
+    }
+
+    #[tokio::test]
+    async fn test_chat_retries_transient_errors() {
+        let server = TestServer::start(vec![
+            MockResponse::status(503, serde_json::json!({"error": {"message": "Overloaded"}})),
+            MockResponse::json(openai_completion("Paris.", "stop")),
+        ])
+        .await;
+        let mut config = OpenAIConfig::new().with_api_key("test-key").with_base_url(server.url());
+        config.base = config.base.with_max_retries(1);
+        let provider = OpenAIProvider::new(config);
+
+        let resp = provider
+            .prompt(ModelConfig::new("gpt-4o-mini"), "Capital of France?".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(resp.text, "Paris.");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].json(), requests[1].json());
+    }
+
+    #[tokio::test]
+    async fn test_chat_timeout() {
+        // Accept connections but never answer
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.push(stream);
+            }
+        });
+
+        let mut config = OpenAIConfig::new().with_api_key("test-key").with_base_url(url);
+        config.base = config.base.with_timeout(1).with_max_retries(0);
+        let provider = OpenAIProvider::new(config);
+
+        let result = provider
+            .prompt(ModelConfig::new("gpt-4o-mini"), "Hello".to_string())
+            .await;
+
+        match result {
+            Err(error @ OrchestraError::Timeout { .. }) => {
+                assert!(error.is_retryable());
+                // The transport error is reported once, as the source
+                let source = std::error::Error::source(&error).unwrap().to_string();
+                assert!(!error.to_string().contains(&source));
+            }
+            other => panic!("Expected timeout error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_multiple_choices() {
+        let mut response = openai_completion("Heads", "stop");
+        response["choices"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({
+                "index": 1,
+                "message": {"role": "assistant", "content": "Tails"},
+                "finish_reason": "length"
+            }));
+        let server = TestServer::start(vec![MockResponse::json(response)]).await;
+        let provider = OpenAIProvider::new(
+            OpenAIConfig::new()
+                .with_api_key("test-key")
+                .with_base_url(server.url()),
+        );
+
+        let model_config = ModelConfig::new("gpt-4o-mini")
+            .with_candidate_count(2)
+            .unwrap();
+        let resp = provider
+            .prompt(model_config, "Flip a coin".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(server.requests()[0].json()["n"], 2);
+        assert_eq!(resp.text, "Heads");
+        assert_eq!(resp.candidates.len(), 2);
+        assert_eq!(resp.candidates[1].index, 1);
+        assert_eq!(resp.candidates[1].text, "Tails");
+        assert_eq!(
+            resp.candidates[1].finish_reason,
+            Some(FinishReason::MaxTokens)
+        );
+    }
+
+    #[test]
+    fn test_tool_message_mapping() {
+        let call = ToolCall {
+            id: "call_abc".to_string(),
+            call_id: Some("call_abc".to_string()),
+            function: ToolFunction {
+                name: "get_weather".to_string(),
+                arguments: serde_json::json!({"city": "Paris"}),
+            },
+        };
+        let config = ModelConfig::new("gpt-4o-mini").with_tool(ToolDefinition::new(
+            "get_weather",
+            "Get the current weather",
+            serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        ));
+
+        let body = serde_json::to_value(OpenAIRequestBody::from_model_config(
+            &config,
+            &[
+                Message::human("Weather in Paris?"),
+                Message::assistant_tool_calls(None::<String>, vec![call]),
+                Message::tool("call_abc", "get_weather", serde_json::json!({"temperature": 21})),
+            ],
+        ).unwrap())
+        .unwrap();
+
+        // A tool message must follow the assistant turn that requested it
+        assert_eq!(
+            body["messages"],
+            serde_json::json!([
+                {"role": "user", "content": "Weather in Paris?"},
+                {
+                    "role": "assistant",
+                    "tool_calls": [{
+                        "id": "call_abc",
+                        "type": "function",
+                        "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+                    }]
+                },
+                {"role": "tool", "content": "{\"temperature\":21}", "tool_call_id": "call_abc"}
+            ])
+        );
+        assert_eq!(body["tools"][0]["type"], "function");
+        assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+    }
+
+    #[test]
+    fn test_tool_without_parameters_mapping() {
+        let config = ModelConfig::new("gpt-4o-mini")
+            .with_tool(ToolDefinition::without_parameters("get_time", "Get the current time"));
+
+        let body = serde_json::to_value(
+            OpenAIRequestBody::from_model_config(&config, &[Message::human("What time is it?")])
+                .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            body["tools"][0],
+            serde_json::json!({
+                "type": "function",
+                "function": {"name": "get_time", "description": "Get the current time"}
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_tool_call_response() {
+        let server = TestServer::start(vec![MockResponse::json(serde_json::json!({
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_abc",
+                        "type": "function",
+                        "function": {"name": "get_weather", "arguments": "{\"city\": \"Paris\"}"}
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }]
+        }))])
+        .await;
+        let provider = OpenAIProvider::new(
+            OpenAIConfig::new()
+                .with_api_key("test-key")
+                .with_base_url(server.url()),
+        );
+
+        let resp = provider
+            .prompt(ModelConfig::new("gpt-4o-mini"), "Weather in Paris?".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(resp.finish_reason, Some(FinishReason::ToolCalls));
+        assert_eq!(resp.tool_calls.len(), 1);
+        assert_eq!(resp.tool_calls[0].id, "call_abc");
+        assert_eq!(resp.tool_calls[0].function.name, "get_weather");
+        assert_eq!(resp.tool_calls[0].function.arguments, serde_json::json!({"city": "Paris"}));
+    }
+
+    #[test]
+    fn test_response_format_mapping() {
+        let schema = serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}});
+
+        let body = serde_json::to_value(OpenAIRequestBody::from_model_config(
+            &ModelConfig::new("gpt-4o-mini").with_response_schema(schema.clone()),
+            &[Message::human("Hi")],
+        ).unwrap())
+        .unwrap();
+        assert_eq!(
+            body["response_format"],
+            serde_json::json!({
+                "type": "json_schema",
+                "json_schema": {"name": "response", "schema": schema}
+            })
+        );
+
+        let body = serde_json::to_value(OpenAIRequestBody::from_model_config(
+            &ModelConfig::new("gpt-4o-mini").with_response_mime_type("application/json"),
+            &[Message::human("Hi")],
+        ).unwrap())
+        .unwrap();
+        assert_eq!(body["response_format"], serde_json::json!({"type": "json_object"}));
+        assert_eq!(
+            body["messages"][0],
+            serde_json::json!({"role": "system", "content": "Respond with a valid JSON object."})
+        );
+
+        let body = serde_json::to_value(OpenAIRequestBody::from_model_config(
+            &ModelConfig::new("gpt-4o-mini"),
+            &[Message::human("Hi")],
+        ).unwrap())
+        .unwrap();
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[test]
+    fn test_json_mode_mentions_json() {
+        let config = ModelConfig::new("gpt-4o-mini").with_response_mime_type("application/json");
+
+        // JSON mode is rejected unless a message asks for JSON
+        let body = serde_json::to_value(OpenAIRequestBody::from_model_config(
+            &config.clone().with_system_instruction("Be concise."),
+            &[Message::human("What is the capital of France?")],
+        ).unwrap())
+        .unwrap();
+        assert_eq!(
+            body["messages"][0]["content"],
+            "Be concise.\n\nRespond with a valid JSON object."
+        );
+
+        let body = serde_json::to_value(OpenAIRequestBody::from_model_config(
+            &config,
+            &[Message::human("Reply in json with the capital of France")],
+        ).unwrap())
+        .unwrap();
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+        assert_eq!(body["messages"][0]["role"], "user");
+    }
+
+    #[test]
+    fn test_reasoning_model_request_body() {
+        let config = ModelConfig::new("o4-mini").with_max_tokens(256);
+
+        let body = serde_json::to_value(
+            OpenAIRequestBody::from_model_config(&config, &[Message::human("Hi")]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(body["model"], "o4-mini");
+        assert_eq!(body["max_completion_tokens"], 256);
+        assert!(body.get("max_tokens").is_none());
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("top_p").is_none());
+
+        // Other models keep the sampling settings and `max_tokens`
+        let body = serde_json::to_value(
+            OpenAIRequestBody::from_model_config(&config.with_name("gpt-4o"), &[Message::human("Hi")])
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(body["max_tokens"], 256);
+        assert!(body.get("max_completion_tokens").is_none());
+        assert!(body.get("temperature").is_some());
+        assert!(body.get("top_p").is_some());
+    }
+
+    #[test]
+    fn test_image_message_mapping() {
+        let config = ModelConfig::new("gpt-4o-mini");
+        let message = Message::human_with_parts(vec![
+            ContentPart::text("What is this?"),
+            ContentPart::inline_data("image/png", b"png"),
+        ]);
+
+        let body = serde_json::to_value(
+            OpenAIRequestBody::from_model_config(&config, &[message]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            body["messages"][0]["content"],
+            serde_json::json!([
+                {"type": "text", "text": "What is this?"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,cG5n"}}
+            ])
+        );
+
+        // Files other than inline images cannot be sent
+        for part in [
+            ContentPart::inline_data("application/pdf", b"%PDF-"),
+            ContentPart::file_uri("image/png", "https://example.com/files/abc"),
+        ] {
+            let message = Message::human_with_parts(vec![ContentPart::text("Hi"), part]);
+            assert!(matches!(
+                OpenAIRequestBody::from_model_config(&config, &[message]),
+                Err(OrchestraError::InvalidRequest { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_finish_reason_mapping() {
+        for (reason, expected) in [
+            ("stop", FinishReason::Stop),
+            ("length", FinishReason::MaxTokens),
+            ("content_filter", FinishReason::Safety),
+            ("tool_calls", FinishReason::ToolCalls),
+            ("function_call", FinishReason::ToolCalls),
+        ] {
+            let response: OpenAIChatResponse =
+                serde_json::from_value(openai_completion("", reason)).unwrap();
+            assert_eq!(response.choices[0].finish_reason(), Some(expected));
+        }
+    }
+}
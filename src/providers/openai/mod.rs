@@ -0,0 +1,7 @@
+mod compatible;
+mod r#impl;
+mod types;
+
+pub use compatible::*;
+pub use r#impl::*;
+pub use types::*;
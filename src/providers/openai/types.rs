@@ -0,0 +1,405 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{OrchestraError, Result},
+    messages::{ContentPart, Message, MessageContent, ToolCall, ToolFunction},
+    model::ModelConfig,
+    providers::types::{FinishReason, Usage, unsupported_content},
+    tools::ToolDefinition,
+};
+
+pub const PREDEFINED_MODELS: &[&str] = &[
+    "gpt-4.1",
+    "gpt-4.1-mini",
+    "gpt-4.1-nano",
+    "gpt-4o",
+    "gpt-4o-mini",
+    "o4-mini",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIRequestBody {
+    pub model: String,
+    pub messages: Vec<OpenAIMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Replaces `max_tokens` for reasoning models, which reject it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    /// Number of choices to generate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    /// Requested output format, for JSON mode and structured outputs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<serde_json::Value>,
+    /// Functions the model may call
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tools: Vec<OpenAITool>,
+}
+
+impl OpenAIRequestBody {
+    /// Build a request body from the model configuration and the messages to send.
+    ///
+    /// The system instruction, if any, is sent as the first `system` message.
+    ///
+    /// JSON mode (`json_object`) is rejected unless the messages mention JSON, so an
+    /// instruction to reply in JSON is added to the system message when they don't.
+    ///
+    /// Inline images on user turns are sent as `image_url` parts; other files are
+    /// rejected.
+    ///
+    /// o-series reasoning models reject `temperature`, `top_p` and `max_tokens`, so
+    /// the sampling settings are left out for them and the token limit is sent as
+    /// `max_completion_tokens`. Other models keep `max_tokens`, which OpenAI-compatible
+    /// servers understand.
+    pub fn from_model_config(config: &ModelConfig, messages: &[Message]) -> Result<Self> {
+        let response_format = response_format(config);
+        let mut system_instruction = config.system_instruction.clone();
+
+        let json_mode = response_format
+            .as_ref()
+            .is_some_and(|format| format["type"] == "json_object");
+        let mentions_json = system_instruction
+            .iter()
+            .cloned()
+            .chain(messages.iter().map(Message::content_text))
+            .any(|text| text.to_lowercase().contains("json"));
+        if json_mode && !mentions_json {
+            let instruction = system_instruction.get_or_insert_with(String::new);
+            if !instruction.is_empty() {
+                instruction.push_str("\n\n");
+            }
+            instruction.push_str(JSON_MODE_INSTRUCTION);
+        }
+
+        let system = system_instruction.map(|instruction| OpenAIMessage {
+            role: "system".to_string(),
+            content: Some(OpenAIContent::Text(instruction)),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        });
+
+        let reasoning = is_reasoning_model(&config.name);
+
+        Ok(Self {
+            model: config.name.clone(),
+            messages: system
+                .into_iter()
+                .map(Ok)
+                .chain(messages.iter().map(OpenAIMessage::try_from))
+                .collect::<Result<_>>()?,
+            temperature: (!reasoning).then_some(config.temperature),
+            top_p: (!reasoning).then_some(config.top_p),
+            max_tokens: config.max_tokens.filter(|_| !reasoning),
+            max_completion_tokens: config.max_tokens.filter(|_| reasoning),
+            stop: if config.stop_sequences.is_empty() {
+                None
+            } else {
+                Some(config.stop_sequences.clone())
+            },
+            n: config.candidate_count,
+            response_format,
+            tools: config.tools.iter().map(OpenAITool::from).collect(),
+        })
+    }
+}
+
+/// Whether `model` is an o-series reasoning model, e.g. `o1`, `o3-mini` or `o4-mini`
+fn is_reasoning_model(model: &str) -> bool {
+    let mut chars = model.chars();
+    chars.next() == Some('o') && chars.next().is_some_and(|c| c.is_ascii_digit())
+}
+
+/// Added to the system message in JSON mode when no message mentions JSON
+const JSON_MODE_INSTRUCTION: &str = "Respond with a valid JSON object.";
+
+/// Map the configured response MIME type and schema to `response_format`
+fn response_format(config: &ModelConfig) -> Option<serde_json::Value> {
+    match &config.response_schema {
+        Some(schema) => Some(serde_json::json!({
+            "type": "json_schema",
+            "json_schema": { "name": "response", "schema": schema },
+        })),
+        None if config.wants_json() => Some(serde_json::json!({ "type": "json_object" })),
+        None => None,
+    }
+}
+
+/// A function the model may call, in the `tools` array
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAITool {
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    pub function: OpenAIFunctionDefinition,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIFunctionDefinition {
+    pub name: String,
+    pub description: String,
+    /// Left out for functions without parameters; OpenAI rejects `null`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+}
+
+impl From<&ToolDefinition> for OpenAITool {
+    fn from(tool: &ToolDefinition) -> Self {
+        OpenAITool {
+            tool_type: "function".to_string(),
+            function: OpenAIFunctionDefinition {
+                name: tool.name.clone(),
+                description: tool.description.clone(),
+                parameters: tool.has_parameters().then(|| tool.parameters.clone()),
+            },
+        }
+    }
+}
+
+/// A function call requested by the model, sent back on the assistant's turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "function_type")]
+    pub tool_type: String,
+    pub function: OpenAIFunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIFunctionCall {
+    pub name: String,
+    /// The arguments as a JSON-encoded string
+    pub arguments: String,
+}
+
+fn function_type() -> String {
+    "function".to_string()
+}
+
+impl From<&ToolCall> for OpenAIToolCall {
+    fn from(call: &ToolCall) -> Self {
+        OpenAIToolCall {
+            id: call.call_id.clone().unwrap_or_else(|| call.id.clone()),
+            tool_type: function_type(),
+            function: OpenAIFunctionCall {
+                name: call.function.name.clone(),
+                arguments: call.function.arguments.to_string(),
+            },
+        }
+    }
+}
+
+impl From<&OpenAIToolCall> for ToolCall {
+    fn from(call: &OpenAIToolCall) -> Self {
+        // Models occasionally produce arguments that are not valid JSON; keep them as
+        // a string so tools can report the problem
+        let arguments = serde_json::from_str(&call.function.arguments)
+            .unwrap_or_else(|_| serde_json::Value::String(call.function.arguments.clone()));
+
+        ToolCall {
+            id: call.id.clone(),
+            call_id: Some(call.id.clone()),
+            function: ToolFunction {
+                name: call.function.name.clone(),
+                arguments,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIMessage {
+    pub role: String,
+    /// May be omitted on assistant turns that only call tools
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<OpenAIContent>,
+    /// Function calls made on an assistant turn
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tool_calls: Vec<OpenAIToolCall>,
+    /// The tool call a `tool` message answers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+/// Message content: a plain string, or typed parts when it carries images
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OpenAIContent {
+    Text(String),
+    Parts(Vec<OpenAIContentPart>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OpenAIContentPart {
+    Text { text: String },
+    ImageUrl { image_url: OpenAIImageUrl },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIImageUrl {
+    /// An `https` URL or a `data:` URL holding the base64-encoded image
+    pub url: String,
+}
+
+impl OpenAIContent {
+    /// Map the content of a user turn, sending inline images as data URLs
+    fn from_user_content(content: &MessageContent) -> Result<Self> {
+        let parts = match content {
+            MessageContent::Parts(parts) if content.media_parts().next().is_some() => parts,
+            _ => return Ok(Self::Text(content.to_text())),
+        };
+
+        parts
+            .iter()
+            .map(|part| match part {
+                ContentPart::Text(text) => Ok(OpenAIContentPart::Text { text: text.clone() }),
+                ContentPart::InlineData { mime_type, data } if mime_type.starts_with("image/") => {
+                    Ok(OpenAIContentPart::ImageUrl {
+                        image_url: OpenAIImageUrl {
+                            url: format!("data:{};base64,{}", mime_type, data),
+                        },
+                    })
+                }
+                part => Err(unsupported_content("openai", part)),
+            })
+            .collect::<Result<_>>()
+            .map(Self::Parts)
+    }
+}
+
+impl TryFrom<&Message> for OpenAIMessage {
+    type Error = OrchestraError;
+
+    fn try_from(msg: &Message) -> Result<Self> {
+        Ok(match msg {
+            Message::Human(h) => OpenAIMessage {
+                role: "user".to_string(),
+                content: Some(OpenAIContent::from_user_content(&h.content)?),
+                tool_calls: Vec::new(),
+                tool_call_id: None,
+            },
+            Message::Assistant(a) => {
+                if let Some(part) = a.content.media_parts().next() {
+                    return Err(unsupported_content("openai", part));
+                }
+
+                let text = a.content.to_text();
+                let tool_calls: Vec<OpenAIToolCall> =
+                    a.content.tool_calls().iter().map(OpenAIToolCall::from).collect();
+
+                OpenAIMessage {
+                    role: "assistant".to_string(),
+                    content: (!text.is_empty() || tool_calls.is_empty())
+                        .then_some(OpenAIContent::Text(text)),
+                    tool_calls,
+                    tool_call_id: None,
+                }
+            }
+            Message::System(s) => OpenAIMessage {
+                role: "system".to_string(),
+                content: Some(OpenAIContent::Text(s.content.clone())),
+                tool_calls: Vec::new(),
+                tool_call_id: None,
+            },
+            Message::Tool(t) => OpenAIMessage {
+                role: "tool".to_string(),
+                content: Some(OpenAIContent::Text(t.content.to_text())),
+                tool_calls: Vec::new(),
+                tool_call_id: Some(t.id.clone()),
+            },
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAIChatResponse {
+    pub id: Option<String>,
+    pub model: Option<String>,
+    #[serde(default)]
+    pub choices: Vec<OpenAIChoice>,
+    pub usage: Option<OpenAIUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAIChoice {
+    pub index: u32,
+    pub message: OpenAIResponseMessage,
+    pub finish_reason: Option<String>,
+}
+
+impl OpenAIChoice {
+    /// Get the normalized finish reason, if the choice has one
+    pub fn finish_reason(&self) -> Option<FinishReason> {
+        self.finish_reason.as_deref().map(|reason| match reason {
+            "stop" => FinishReason::Stop,
+            "length" => FinishReason::MaxTokens,
+            "content_filter" => FinishReason::Safety,
+            "tool_calls" | "function_call" => FinishReason::ToolCalls,
+            other => FinishReason::Other(other.to_string()),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAIResponseMessage {
+    pub role: String,
+    pub content: Option<String>,
+    pub refusal: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<OpenAIToolCall>,
+}
+
+impl OpenAIResponseMessage {
+    /// The reply text, or the refusal message if the model declined to answer
+    pub fn text(&self) -> Option<&str> {
+        self.content.as_deref().or(self.refusal.as_deref())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAIUsage {
+    #[serde(default)]
+    pub prompt_tokens: u32,
+    #[serde(default)]
+    pub completion_tokens: u32,
+    #[serde(default)]
+    pub total_tokens: u32,
+}
+
+impl From<&OpenAIUsage> for Usage {
+    fn from(usage: &OpenAIUsage) -> Self {
+        Usage {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            total_tokens: usage.total_tokens,
+            thoughts_tokens: None,
+        }
+    }
+}
+
+/// Error body returned by the OpenAI API, e.g. `{"error": {"message": ...}}`
+#[derive(Debug, Deserialize)]
+pub struct OpenAIErrorResponse {
+    pub error: OpenAIError,
+}
+
+impl OpenAIErrorResponse {
+    /// The message of an error body, if it has the expected shape
+    pub fn parse_message(body: &str) -> Option<String> {
+        serde_json::from_str::<Self>(body).ok().map(|e| e.error.message)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAIError {
+    pub message: String,
+    #[serde(rename = "type")]
+    pub error_type: Option<String>,
+    pub code: Option<serde_json::Value>,
+}
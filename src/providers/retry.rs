@@ -0,0 +1,292 @@
+//! Retrying transient HTTP failures.
+//!
+//! [`RetryPolicy`] resends a request after connection errors, timeouts and
+//! `429`/`500`/`502`/`503`/`504`/`529` responses, waiting an exponentially growing,
+//! jittered delay between attempts. A `Retry-After` header on the response takes
+//! precedence over the computed delay, up to `max_backoff`. Other failures, such as
+//! `400` or `401`, are returned immediately since repeating the request cannot fix them.
+//! Each retry is logged at `warn` level.
+
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, SystemTime},
+};
+
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+use crate::{
+    error::{OrchestraError, Result},
+    providers::{config::ProviderConfig, http::retry_after},
+};
+
+/// When and how often to retry a failed request
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; `0` disables retrying
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every further retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between attempts, including one asked for by `Retry-After`
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy allowing `max_retries` retries with the default backoff
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    /// Create a policy from a provider's `max_retries` setting
+    pub fn from_config(config: &ProviderConfig) -> Self {
+        Self::new(config.get_max_retries())
+    }
+
+    /// Set the delay before the first retry
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Set the upper bound for the delay between attempts
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// The delay before retry number `retry` (starting at 0): half of the exponential
+    /// backoff plus a random share of the other half, so concurrent clients spread out
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+
+        exponential / 2 + exponential.mul_f64(jitter() / 2.0)
+    }
+
+    /// Whether a response status is worth retrying; see [`OrchestraError::is_retryable_status`]
+    pub fn is_retryable_status(status: StatusCode) -> bool {
+        OrchestraError::is_retryable_status(status.as_u16())
+    }
+
+    /// Whether a transport error is worth retrying; see [`OrchestraError::is_retryable_transport`]
+    pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+        OrchestraError::is_retryable_transport(error)
+    }
+
+    /// Send `request`, retrying transient failures.
+    ///
+    /// Unsuccessful responses are turned into errors by `into_error`. When the retries
+    /// run out on a transient error, it is wrapped in [`OrchestraError::RetryLimit`]
+    /// together with the number of attempts made. Errors that retrying cannot fix are
+    /// returned unchanged, even after earlier transient failures.
+    pub(crate) async fn send<F, Fut>(&self, request: RequestBuilder, into_error: F) -> Result<Response>
+    where
+        F: Fn(Response) -> Fut,
+        Fut: Future<Output = OrchestraError>,
+    {
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+
+            let attempt = request.try_clone().ok_or_else(|| {
+                OrchestraError::generic("Request with a streaming body cannot be retried")
+            })?;
+
+            let (error, retryable, delay) = match attempt.send().await {
+                Ok(resp) if resp.status().is_success() => return Ok(resp),
+                Ok(resp) => {
+                    let retryable = Self::is_retryable_status(resp.status());
+                    let delay = retry_after(resp.headers());
+                    (into_error(resp).await, retryable, delay)
+                }
+                Err(e) => {
+                    let retryable = Self::is_retryable_error(&e);
+                    (OrchestraError::from(e), retryable, None)
+                }
+            };
+
+            if !retryable {
+                if attempts > 1 {
+                    log::debug!("request failed permanently after {} attempts: {}", attempts, error);
+                }
+                return Err(error);
+            }
+
+            if attempts > self.max_retries {
+                return Err(if attempts > 1 {
+                    OrchestraError::RetryLimit {
+                        attempts,
+                        source: Box::new(error),
+                    }
+                } else {
+                    error
+                });
+            }
+
+            let delay = delay.map_or_else(|| self.backoff(attempts - 1), |d| d.min(self.max_backoff));
+            log::warn!("attempt {} failed, retrying in {:?}: {}", attempts, delay, error);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// A pseudo-random number in `[0, 1]`, good enough to spread out retries
+fn jitter() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    hasher.write_u32(nanos);
+
+    hasher.finish() as f64 / u64::MAX as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::test_server::{MockResponse, TestServer};
+
+    fn fast_policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy::new(max_retries)
+            .with_initial_backoff(Duration::from_millis(1))
+            .with_max_backoff(Duration::from_millis(5))
+    }
+
+    async fn status_error(resp: Response) -> OrchestraError {
+        OrchestraError::provider("test", &format!("HTTP {}", resp.status().as_u16()))
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy::new(5)
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(1000));
+
+        for (retry, base) in [(0, 100), (1, 200), (2, 400), (3, 800), (4, 1000), (10, 1000)] {
+            let delay = policy.backoff(retry);
+            let base = Duration::from_millis(base);
+            assert!(delay >= base / 2 && delay <= base, "{:?} for retry {}", delay, retry);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_statuses() {
+        let server = TestServer::start(vec![
+            MockResponse::status(503, serde_json::json!({})),
+            MockResponse::status(429, serde_json::json!({})).with_header("Retry-After", "0"),
+            MockResponse::json(serde_json::json!({"ok": true})),
+        ])
+        .await;
+
+        let request = reqwest::Client::new().post(server.url()).json(&serde_json::json!({"n": 1}));
+        let resp = fast_policy(3).send(request, status_error).await.unwrap();
+
+        assert_eq!(resp.status(), 200);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|r| r.json() == serde_json::json!({"n": 1})));
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_client_errors() {
+        let server = TestServer::start(vec![
+            MockResponse::status(401, serde_json::json!({})),
+            MockResponse::json(serde_json::json!({"ok": true})),
+        ])
+        .await;
+
+        let request = reqwest::Client::new().get(server.url());
+        let result = fast_policy(3).send(request, status_error).await;
+
+        assert!(matches!(result, Err(OrchestraError::Provider { ref message, .. }) if message == "HTTP 401"));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reports_attempts_when_exhausted() {
+        let server = TestServer::start(vec![
+            MockResponse::status(500, serde_json::json!({})),
+            MockResponse::status(500, serde_json::json!({})),
+            MockResponse::status(500, serde_json::json!({})),
+        ])
+        .await;
+
+        let request = reqwest::Client::new().get(server.url());
+        match fast_policy(2).send(request, status_error).await {
+            Err(OrchestraError::RetryLimit { attempts, source }) => {
+                assert_eq!(attempts, 3);
+                assert!(matches!(*source, OrchestraError::Provider { .. }));
+            }
+            other => panic!("Expected retry limit error, got {:?}", other),
+        }
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_returns_permanent_error_after_retry() {
+        let server = TestServer::start(vec![
+            MockResponse::status(503, serde_json::json!({})),
+            MockResponse::status(400, serde_json::json!({})),
+            MockResponse::json(serde_json::json!({"ok": true})),
+        ])
+        .await;
+
+        let request = reqwest::Client::new().get(server.url());
+        let result = fast_policy(3).send(request, status_error).await;
+
+        // Callers can match on the permanent error however many attempts preceded it
+        assert!(matches!(result, Err(OrchestraError::Provider { ref message, .. }) if message == "HTTP 400"));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_is_capped() {
+        let server = TestServer::start(vec![
+            MockResponse::status(429, serde_json::json!({})).with_header("Retry-After", "3600"),
+            MockResponse::json(serde_json::json!({"ok": true})),
+        ])
+        .await;
+
+        let request = reqwest::Client::new().get(server.url());
+        let resp = tokio::time::timeout(
+            Duration::from_secs(5),
+            fast_policy(1).send(request, status_error),
+        )
+        .await
+        .expect("Retry-After should be capped at max_backoff")
+        .unwrap();
+
+        assert_eq!(resp.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_retries_connection_errors() {
+        // Nothing listens on the port of a dropped listener
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let request = reqwest::Client::new().get(url);
+        let result = fast_policy(1).send(request, status_error).await;
+
+        assert!(matches!(result, Err(OrchestraError::RetryLimit { attempts: 2, .. })));
+    }
+}
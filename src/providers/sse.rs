@@ -0,0 +1,179 @@
+//! Incremental parser for `text/event-stream` (server-sent events) bodies.
+//!
+//! Streaming endpoints deliver their payload as server-sent events, but the bytes
+//! arrive in arbitrary TCP-sized chunks: a single event can be split across several
+//! reads and one read can carry several events. [`SseParser`] buffers partial input
+//! and only yields events once their terminating blank line has been received.
+
+/// A single server-sent event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The event type from the `event:` field, if any
+    pub event: Option<String>,
+    /// The event payload; multiple `data:` lines are joined with `\n`
+    pub data: String,
+    /// The last event id from the `id:` field, if any
+    pub id: Option<String>,
+}
+
+/// Incremental server-sent events parser.
+///
+/// Feed raw body chunks with [`SseParser::push`] and call [`SseParser::finish`] once
+/// the body ends. Lines may be terminated by `\n` or `\r\n`.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    /// Bytes received that do not yet form a complete line
+    buffer: Vec<u8>,
+    /// Fields collected for the event currently being parsed
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl SseParser {
+    /// Create a new, empty parser
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of the response body and return every event it completed.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(newline) = self.buffer.iter().position(|&b| b == b'\n') {
+            let mut line: Vec<u8> = self.buffer.drain(..=newline).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+
+            if let Some(event) = self.process_line(&String::from_utf8_lossy(&line)) {
+                events.push(event);
+            }
+        }
+
+        events
+    }
+
+    /// Signal the end of the body and return the final event, if the body did not
+    /// end with a blank line.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        if !self.buffer.is_empty() {
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).into_owned();
+            let line = line.strip_suffix('\r').unwrap_or(&line).to_string();
+            if let Some(event) = self.process_line(&line) {
+                return Some(event);
+            }
+        }
+
+        self.dispatch()
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+
+        // Lines starting with a colon are comments (often used as keep-alives)
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" => self.id = Some(value.to_string()),
+            // `retry` and unknown fields are ignored
+            _ => {}
+        }
+
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        if self.data.is_empty() && self.event.is_none() {
+            return None;
+        }
+
+        Some(SseEvent {
+            event: self.event.take(),
+            data: std::mem::take(&mut self.data).join("\n"),
+            id: self.id.take(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_parser_single_event() {
+        let mut parser = SseParser::new();
+        let events = parser.push(b"data: {\"a\":1}\n\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "{\"a\":1}");
+        assert!(events[0].event.is_none());
+    }
+
+    #[test]
+    fn test_sse_parser_split_across_chunks() {
+        let mut parser = SseParser::new();
+
+        assert!(parser.push(b"da").is_empty());
+        assert!(parser.push(b"ta: hel").is_empty());
+        assert!(parser.push(b"lo\r\n").is_empty());
+        let events = parser.push(b"\r\ndata: world\n\n");
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].data, "hello");
+        assert_eq!(events[1].data, "world");
+    }
+
+    #[test]
+    fn test_sse_parser_split_utf8_character() {
+        let mut parser = SseParser::new();
+        let bytes = "data: héllo\n\n".as_bytes();
+        let split = bytes.iter().position(|&b| b == 0xC3).unwrap() + 1;
+
+        assert!(parser.push(&bytes[..split]).is_empty());
+        let events = parser.push(&bytes[split..]);
+
+        assert_eq!(events[0].data, "héllo");
+    }
+
+    #[test]
+    fn test_sse_parser_fields_and_comments() {
+        let mut parser = SseParser::new();
+        let events = parser.push(
+            b": keep-alive\nevent: message_start\nid: 7\ndata: line one\ndata: line two\n\n",
+        );
+
+        assert_eq!(
+            events,
+            vec![SseEvent {
+                event: Some("message_start".to_string()),
+                data: "line one\nline two".to_string(),
+                id: Some("7".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_sse_parser_finish_without_trailing_blank_line() {
+        let mut parser = SseParser::new();
+
+        assert_eq!(parser.push(b"data: first\n\ndata: last").len(), 1);
+        let last = parser.finish().unwrap();
+
+        assert_eq!(last.data, "last");
+        assert!(parser.finish().is_none());
+    }
+}
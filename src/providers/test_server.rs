@@ -0,0 +1,275 @@
+//! Minimal local HTTP stand-in for provider tests.
+//!
+//! [`TestServer`] listens on an ephemeral localhost port, records every request it
+//! receives and replays a queue of canned [`MockResponse`]s in order. Response bodies
+//! can be split into several chunks that are written separately, which lets tests
+//! exercise parsers against data arriving across multiple TCP reads.
+//!
+//! [`fixtures`] holds typical response bodies of each provider's API, for tests to
+//! queue or parse.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// A request received by the [`TestServer`].
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Request target including any query string, e.g. `/v1/chat/completions`
+    pub path: String,
+    /// Header names are lowercased
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// Get a header value by (case-insensitive) name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(|v| v.as_str())
+    }
+
+    /// Parse the request body as JSON
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("request body is not valid JSON")
+    }
+}
+
+/// A canned response replayed by the [`TestServer`].
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// Body chunks, written to the socket one at a time
+    pub chunks: Vec<Vec<u8>>,
+}
+
+impl MockResponse {
+    /// A `200 OK` JSON response
+    pub fn json(body: serde_json::Value) -> Self {
+        Self::status(200, body)
+    }
+
+    /// A JSON response with the given status code
+    pub fn status(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            chunks: vec![body.to_string().into_bytes()],
+        }
+    }
+
+    /// A `200 OK` server-sent events response whose body is written in the given chunks
+    pub fn sse<I, S>(chunks: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Vec<u8>>,
+    {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "text/event-stream".to_string())],
+            chunks: chunks.into_iter().map(|c| c.into()).collect(),
+        }
+    }
+
+    /// Add a response header
+    pub fn with_header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+}
+
+/// A local HTTP server replaying canned responses.
+pub struct TestServer {
+    url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    responses: Arc<Mutex<VecDeque<MockResponse>>>,
+}
+
+impl TestServer {
+    /// Start a server that answers requests with `responses`, in order. Once the
+    /// queue is exhausted every further request receives a `500` response.
+    pub async fn start(responses: Vec<MockResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let responses = Arc::new(Mutex::new(VecDeque::from(responses)));
+
+        let recorded = requests.clone();
+        let queue = responses.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let recorded = recorded.clone();
+                let responses = responses.clone();
+                tokio::spawn(async move {
+                    handle_connection(socket, recorded, responses).await;
+                });
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            responses: queue,
+        }
+    }
+
+    /// Queue another response, e.g. one that refers back to the server's own URL
+    pub fn enqueue(&self, response: MockResponse) {
+        self.responses.lock().unwrap().push_back(response);
+    }
+
+    /// Base URL of the server, e.g. `http://127.0.0.1:4242`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// All requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn handle_connection(
+    mut socket: TcpStream,
+    recorded: Arc<Mutex<Vec<RecordedRequest>>>,
+    responses: Arc<Mutex<VecDeque<MockResponse>>>,
+) {
+    let Some(request) = read_request(&mut socket).await else {
+        return;
+    };
+    recorded.lock().unwrap().push(request);
+
+    let response = responses.lock().unwrap().pop_front().unwrap_or_else(|| {
+        MockResponse::status(500, serde_json::json!({"error": "no canned response left"}))
+    });
+
+    let mut head = format!("HTTP/1.1 {} Test\r\nConnection: close\r\n", response.status);
+    for (key, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", key, value));
+    }
+    head.push_str("\r\n");
+
+    if socket.write_all(head.as_bytes()).await.is_err() {
+        return;
+    }
+    for chunk in &response.chunks {
+        if socket.write_all(chunk).await.is_err() || socket.flush().await.is_err() {
+            return;
+        }
+        // Give the client a chance to observe each chunk as a separate read
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    let _ = socket.shutdown().await;
+}
+
+async fn read_request(socket: &mut TcpStream) -> Option<RecordedRequest> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+
+    let header_end = loop {
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        let n = socket.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&data[..header_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        .collect();
+
+    let content_length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    let mut body = data[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let n = socket.read(&mut buf).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&buf[..n]);
+    }
+
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// Typical successful response bodies of the providers' APIs
+pub mod fixtures {
+    use serde_json::{Value, json};
+
+    /// An OpenAI `/chat/completions` response with a single choice
+    pub fn openai_completion(content: &str, finish_reason: &str) -> Value {
+        json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "model": "gpt-4o-mini",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": content},
+                "finish_reason": finish_reason
+            }],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 5, "total_tokens": 17}
+        })
+    }
+
+    /// An Anthropic `/messages` response with a single text block
+    pub fn anthropic_message(text: &str, stop_reason: &str) -> Value {
+        json!({
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-0",
+            "content": [{"type": "text", "text": text}],
+            "stop_reason": stop_reason,
+            "usage": {"input_tokens": 20, "output_tokens": 8}
+        })
+    }
+
+    /// An Ollama `/api/chat` response to a non-streaming request
+    pub fn ollama_chat(content: &str, done_reason: &str) -> Value {
+        json!({
+            "model": "llama3.2",
+            "created_at": "2025-01-01T00:00:00Z",
+            "message": {"role": "assistant", "content": content},
+            "done": true,
+            "done_reason": done_reason,
+            "prompt_eval_count": 26,
+            "eval_count": 5
+        })
+    }
+
+    /// An Ollama `/api/tags` response listing two local models
+    pub fn ollama_tags() -> Value {
+        json!({
+            "models": [
+                {"name": "llama3.2:latest", "model": "llama3.2:latest", "size": 2019393189u64},
+                {"name": "qwen2.5-coder:7b", "model": "qwen2.5-coder:7b", "size": 4683087332u64}
+            ]
+        })
+    }
+}
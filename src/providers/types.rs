@@ -1,8 +1,20 @@
+use std::pin::Pin;
+
+use futures::Stream;
+
+use crate::{
+    error::{OrchestraError, Result},
+    messages::{ContentPart, ToolCall},
+};
+
 /// This is a list of all provider sources that are supported.
 #[derive(Debug, Clone, Copy)]
 pub enum ProviderSource {
     Gemini,
     OpenAI,
+    OpenAICompatible,
+    Anthropic,
+    Ollama,
 }
 
 impl ProviderSource {
@@ -10,19 +22,233 @@ impl ProviderSource {
         match self {
             ProviderSource::Gemini => "gemini",
             ProviderSource::OpenAI => "openai",
+            ProviderSource::OpenAICompatible => "openai_compatible",
+            ProviderSource::Anthropic => "anthropic",
+            ProviderSource::Ollama => "ollama",
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<ProviderSource> {
         match s.to_lowercase().as_str() {
             "gemini" => Some(ProviderSource::Gemini),
             "openai" => Some(ProviderSource::OpenAI),
+            "openai_compatible" => Some(ProviderSource::OpenAICompatible),
+            "anthropic" => Some(ProviderSource::Anthropic),
+            "ollama" => Some(ProviderSource::Ollama),
             _ => None,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ChatResponse {
     pub text: String,
+    /// Tool calls requested by the model, in the order they were returned
+    pub tool_calls: Vec<ToolCall>,
+    /// Why the model stopped generating, if the provider reported it
+    pub finish_reason: Option<FinishReason>,
+    /// Token usage for the request, if the provider reported it
+    pub usage: Option<Usage>,
+    /// The exact model version that produced the response, e.g. `gpt-4o-mini-2024-07-18`
+    pub model_version: Option<String>,
+    /// Provider-assigned identifier of the response
+    pub response_id: Option<String>,
+    /// Every part of the reply, in the order the model produced them
+    pub parts: Vec<ResponsePart>,
+    /// Every response the model generated, in order. `text`, `tool_calls`, `parts`
+    /// and `finish_reason` above mirror the first one with content, e.g. skipping a
+    /// candidate blocked by safety filters.
+    ///
+    /// Contains more than one entry only when `ModelConfig::candidate_count` was set
+    /// and the provider supports it.
+    pub candidates: Vec<Candidate>,
+}
+
+impl ChatResponse {
+    /// Build a response whose primary reply is the first of `candidates` with content,
+    /// or the first one if none has any
+    pub fn from_candidates(candidates: Vec<Candidate>) -> Self {
+        let primary = candidates
+            .iter()
+            .find(|candidate| !candidate.parts.is_empty())
+            .or(candidates.first())
+            .cloned()
+            .unwrap_or_default();
+
+        Self {
+            text: primary.text,
+            tool_calls: primary.tool_calls,
+            finish_reason: primary.finish_reason,
+            parts: primary.parts,
+            candidates,
+            ..Default::default()
+        }
+    }
+
+    /// Check if the model requested any tool calls
+    pub fn has_tool_calls(&self) -> bool {
+        !self.tool_calls.is_empty()
+    }
+
+    /// The model's thought summary, if thoughts were requested and returned
+    pub fn thought_summary(&self) -> Option<String> {
+        let thoughts: Vec<&str> = self
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                ResponsePart::Thought(thought) => Some(thought.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        (!thoughts.is_empty()).then(|| thoughts.concat())
+    }
+
+    /// Check if the response was cut off by the output token limit
+    pub fn is_truncated(&self) -> bool {
+        self.finish_reason == Some(FinishReason::MaxTokens)
+    }
+}
+
+/// One of the alternative responses generated for a request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Candidate {
+    /// Position of this candidate in the provider's response
+    pub index: u32,
+    /// Concatenation of the candidate's text parts
+    pub text: String,
+    /// The candidate's tool call parts
+    pub tool_calls: Vec<ToolCall>,
+    /// Every part of the candidate, in order
+    pub parts: Vec<ResponsePart>,
+    pub finish_reason: Option<FinishReason>,
+    /// Safety assessment of the candidate, for providers that report one
+    pub safety_ratings: Vec<SafetyRating>,
+}
+
+impl Candidate {
+    /// Build a candidate from its parts, filling `text` and `tool_calls` from them
+    pub fn from_parts(index: u32, parts: Vec<ResponsePart>) -> Self {
+        Self {
+            index,
+            text: ResponsePart::concat_text(&parts),
+            tool_calls: parts
+                .iter()
+                .filter_map(|part| match part {
+                    ResponsePart::ToolCall(call) => Some(call.clone()),
+                    _ => None,
+                })
+                .collect(),
+            parts,
+            ..Default::default()
+        }
+    }
+}
+
+/// A single piece of a model reply.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponsePart {
+    /// Answer text
+    Text(String),
+    /// A tool call requested by the model
+    ToolCall(ToolCall),
+    /// A summary of the model's reasoning, for models that expose one
+    Thought(String),
+    /// Binary data generated by the model, e.g. an image
+    InlineData {
+        mime_type: String,
+        /// Base64-encoded bytes
+        data: String,
+    },
+}
+
+impl ResponsePart {
+    /// Get the answer text of this part, if it is a text part
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Concatenate the text parts, skipping thoughts and all other parts
+    pub fn concat_text(parts: &[ResponsePart]) -> String {
+        parts.iter().filter_map(ResponsePart::as_text).collect()
+    }
+}
+
+/// A provider's assessment of how likely a response is to be harmful in one category.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafetyRating {
+    /// Harm category, e.g. `HARM_CATEGORY_HARASSMENT`
+    pub category: String,
+    /// Probability of harm, e.g. `NEGLIGIBLE`
+    pub probability: String,
+    /// Whether the content was blocked because of this rating
+    pub blocked: bool,
+}
+
+/// Why the model stopped generating, normalized across providers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FinishReason {
+    /// The model reached a natural stopping point or a stop sequence
+    Stop,
+    /// The configured maximum number of output tokens was reached
+    MaxTokens,
+    /// The response was stopped by the provider's safety filters
+    Safety,
+    /// The model stopped to request one or more tool calls
+    ToolCalls,
+    /// Any other provider-specific reason
+    Other(String),
+}
+
+/// Token usage reported by the provider for a single request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+    /// Tokens spent on thinking, for providers that report them separately from
+    /// `completion_tokens`
+    pub thoughts_tokens: Option<u32>,
+}
+
+/// A single incremental event emitted by a streaming chat request.
+///
+/// New kinds of events may be added, so matches need a wildcard arm.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ChatStreamEvent {
+    /// A chunk of generated text, to be appended to what was received so far
+    TextDelta(String),
+    /// A chunk of the model's thought summary, when thoughts were requested
+    ThoughtDelta(String),
+    /// Binary data generated by the model, e.g. an image
+    InlineData {
+        mime_type: String,
+        /// Base64-encoded bytes
+        data: String,
+    },
+    /// A complete tool call requested by the model
+    ToolCall(ToolCall),
+    /// The model finished generating, with the reason it stopped
+    Finish(FinishReason),
+    /// Token usage for the request, usually sent once near the end of the stream
+    Usage(Usage),
+}
+
+/// Stream of incremental events returned by `chat_stream`/`prompt_stream`.
+pub type ChatStream = Pin<Box<dyn Stream<Item = Result<ChatStreamEvent>> + Send>>;
+
+/// The error for a message part a provider cannot send
+pub(crate) fn unsupported_content(provider: &str, part: &ContentPart) -> OrchestraError {
+    let content = match part {
+        ContentPart::Text(_) => "text".to_string(),
+        ContentPart::InlineData { mime_type, .. } => format!("inline {} data", mime_type),
+        ContentPart::FileData { mime_type, .. } => format!("uploaded {} files", mime_type),
+    };
+
+    OrchestraError::invalid_request(format!("{} does not accept {} in messages", provider, content))
 }
@@ -0,0 +1,258 @@
+//! Typed tool arguments.
+//!
+//! [`ToolSchema`] describes how a Rust type is represented in a JSON schema and
+//! [`ToolArgs`] marks a struct that can be used as a tool's full argument list.
+//! With the `derive` feature both can be derived, using the struct's field types
+//! and doc comments to build the schema.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use crate::error::{OrchestraError, Result};
+
+/// A type that can describe itself as a JSON schema.
+pub trait ToolSchema {
+    /// Whether a field of this type must be present in the arguments.
+    ///
+    /// Only `Option<T>` overrides this, so optional fields are left out of the
+    /// schema's `required` list.
+    const REQUIRED: bool = true;
+
+    /// The JSON schema for this type
+    fn json_schema() -> Value;
+}
+
+/// A struct that can be used as the arguments of a tool.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use orchestra_rs::tools::{ToolArgs, ToolDefinition};
+/// use serde::Deserialize;
+///
+/// /// Get the current weather for a city
+/// #[derive(Deserialize, ToolArgs)]
+/// struct WeatherArgs {
+///     /// Name of the city, e.g. "Paris"
+///     city: String,
+///     /// Number of days to forecast
+///     days: Option<u32>,
+/// }
+///
+/// let tool = ToolDefinition::from_args::<WeatherArgs>("get_weather", "Get the weather");
+/// let args = WeatherArgs::from_arguments(&serde_json::json!({"city": "Paris"}))?;
+/// ```
+pub trait ToolArgs: ToolSchema + DeserializeOwned {
+    /// The `object` schema describing the arguments
+    fn parameters_schema() -> Value {
+        Self::json_schema()
+    }
+
+    /// Deserialize the arguments chosen by the model.
+    ///
+    /// Returns [`OrchestraError::InvalidToolArguments`] if they do not match the struct.
+    fn from_arguments(arguments: &Value) -> Result<Self> {
+        // Models sometimes send `null` instead of `{}` for tools without arguments
+        let arguments = if arguments.is_null() {
+            &Value::Object(Map::new())
+        } else {
+            arguments
+        };
+
+        Self::deserialize(arguments).map_err(|e| OrchestraError::InvalidToolArguments {
+            message: e.to_string(),
+            source: Some(e),
+        })
+    }
+}
+
+macro_rules! impl_tool_schema {
+    ($schema_type:literal: $($ty:ty),*) => {
+        $(
+            impl ToolSchema for $ty {
+                fn json_schema() -> Value {
+                    json!({ "type": $schema_type })
+                }
+            }
+        )*
+    };
+}
+
+impl_tool_schema!("string": String, char);
+impl_tool_schema!("boolean": bool);
+impl_tool_schema!("integer": i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_tool_schema!("number": f32, f64);
+
+impl ToolSchema for Value {
+    fn json_schema() -> Value {
+        json!({})
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for Option<T> {
+    const REQUIRED: bool = false;
+
+    fn json_schema() -> Value {
+        T::json_schema()
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for Box<T> {
+    const REQUIRED: bool = T::REQUIRED;
+
+    fn json_schema() -> Value {
+        T::json_schema()
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for Vec<T> {
+    fn json_schema() -> Value {
+        json!({ "type": "array", "items": T::json_schema() })
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for HashMap<String, T> {
+    fn json_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::json_schema() })
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for BTreeMap<String, T> {
+    fn json_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::json_schema() })
+    }
+}
+
+/// Support code for `#[derive(ToolSchema, ToolArgs)]`. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    pub use serde::de::DeserializeOwned;
+    pub use serde_json::Value;
+
+    /// A struct field as seen by the derive macro
+    pub struct Field {
+        pub name: &'static str,
+        pub description: Option<&'static str>,
+        pub schema: Value,
+        pub required: bool,
+    }
+
+    /// Build the `object` schema of a struct
+    pub fn object_schema(description: Option<&str>, fields: Vec<Field>) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+
+        for field in fields {
+            let schema = with_description(field.schema, field.description);
+            properties.insert(field.name.to_string(), schema);
+            if field.required {
+                required.push(Value::from(field.name));
+            }
+        }
+
+        let schema = json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        });
+        with_description(schema, description)
+    }
+
+    /// Build the schema of an enum made of unit variants
+    pub fn enum_schema(description: Option<&str>, variants: &[&str]) -> Value {
+        with_description(json!({ "type": "string", "enum": variants }), description)
+    }
+
+    fn with_description(mut schema: Value, description: Option<&str>) -> Value {
+        if let (Some(description), Some(object)) = (description, schema.as_object_mut()) {
+            object.insert("description".to_string(), Value::from(description));
+        }
+        schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Args {
+        city: String,
+        days: Option<u32>,
+    }
+
+    impl ToolSchema for Args {
+        fn json_schema() -> Value {
+            __private::object_schema(
+                Some("Weather lookup"),
+                vec![
+                    __private::Field {
+                        name: "city",
+                        description: Some("City name"),
+                        schema: String::json_schema(),
+                        required: String::REQUIRED,
+                    },
+                    __private::Field {
+                        name: "days",
+                        description: None,
+                        schema: Option::<u32>::json_schema(),
+                        required: Option::<u32>::REQUIRED,
+                    },
+                ],
+            )
+        }
+    }
+
+    impl ToolArgs for Args {}
+
+    #[test]
+    fn test_primitive_schemas() {
+        assert_eq!(String::json_schema(), json!({"type": "string"}));
+        assert_eq!(u8::json_schema(), json!({"type": "integer"}));
+        assert_eq!(f64::json_schema(), json!({"type": "number"}));
+        assert_eq!(
+            Vec::<bool>::json_schema(),
+            json!({"type": "array", "items": {"type": "boolean"}})
+        );
+    }
+
+    #[test]
+    fn test_object_schema() {
+        assert_eq!(
+            Args::parameters_schema(),
+            json!({
+                "type": "object",
+                "description": "Weather lookup",
+                "properties": {
+                    "city": {"type": "string", "description": "City name"},
+                    "days": {"type": "integer"}
+                },
+                "required": ["city"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_arguments() {
+        let args = Args::from_arguments(&json!({"city": "Paris"})).unwrap();
+        assert_eq!(
+            args,
+            Args {
+                city: "Paris".to_string(),
+                days: None
+            }
+        );
+
+        match Args::from_arguments(&json!({"city": 42})) {
+            Err(OrchestraError::InvalidToolArguments { message, source }) => {
+                assert!(message.contains("invalid type"), "{}", message);
+                assert!(source.is_some());
+            }
+            other => panic!("Expected invalid tool arguments error, got {:?}", other),
+        }
+    }
+}
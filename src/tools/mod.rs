@@ -0,0 +1,469 @@
+//! # Tools
+//!
+//! Types for declaring tools (functions) that a model may call.
+//!
+//! A [`ToolDefinition`] describes a function by name, a natural-language description
+//! and a JSON schema for its parameters. Definitions are attached to a request via
+//! [`ModelConfig::with_tool`](crate::model::ModelConfig::with_tool); calls requested
+//! by the model come back as [`ToolCall`](crate::messages::ToolCall)s on the
+//! [`ChatResponse`](crate::providers::types::ChatResponse).
+//!
+//! For executable tools, implement the [`Tool`] trait and collect them in a
+//! [`ToolRegistry`]. [`LLM::run_with_tools`](crate::llm::LLM::run_with_tools) then
+//! drives the whole request / execute / respond loop.
+//!
+//! Instead of writing argument schemas by hand, implement [`ToolArgs`] for a struct
+//! (or derive it with the `derive` feature) and build the definition with
+//! [`ToolDefinition::from_args`]. [`FnTool`] goes one step further and turns an async
+//! function taking such a struct into a [`Tool`], so no trait needs implementing:
+//!
+//! ```rust,ignore
+//! /// Get the current weather for a city
+//! #[derive(Deserialize, ToolArgs)]
+//! struct WeatherArgs {
+//!     /// Name of the city, e.g. "Paris"
+//!     city: String,
+//! }
+//!
+//! let registry = ToolRegistry::new().with_fn(
+//!     "get_weather",
+//!     "Get the current weather for a city",
+//!     |args: WeatherArgs| async move { Ok(serde_json::json!({ "city": args.city, "temperature": 21 })) },
+//! );
+//! ```
+//!
+//! ## Examples
+//!
+//! ```rust,no_run
+//! use async_trait::async_trait;
+//! use orchestra_rs::{
+//!     error::Result,
+//!     llm::LLM,
+//!     messages::Message,
+//!     tools::{Tool, ToolRegistry},
+//! };
+//!
+//! struct Add;
+//!
+//! #[async_trait]
+//! impl Tool for Add {
+//!     fn name(&self) -> &str {
+//!         "add"
+//!     }
+//!
+//!     fn description(&self) -> &str {
+//!         "Add two numbers"
+//!     }
+//!
+//!     fn parameters(&self) -> serde_json::Value {
+//!         serde_json::json!({
+//!             "type": "object",
+//!             "properties": {"a": {"type": "number"}, "b": {"type": "number"}},
+//!             "required": ["a", "b"]
+//!         })
+//!     }
+//!
+//!     async fn call(&self, arguments: serde_json::Value) -> Result<serde_json::Value> {
+//!         let sum = arguments["a"].as_f64().unwrap_or(0.0) + arguments["b"].as_f64().unwrap_or(0.0);
+//!         Ok(serde_json::json!({ "sum": sum }))
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     let llm = LLM::gemini("gemini-2.5-flash");
+//!     let registry = ToolRegistry::new().with_tool(Add);
+//!
+//!     let response = llm
+//!         .run_with_tools(Message::human("What is 1234 + 4321?"), vec![], &registry, 5)
+//!         .await?;
+//!     println!("{}", response.text);
+//!     Ok(())
+//! }
+//! ```
+
+mod args;
+
+pub use args::{ToolArgs, ToolSchema, __private};
+#[cfg(feature = "derive")]
+pub use orchestra_rs_derive::{ToolArgs, ToolSchema};
+
+use std::{collections::BTreeMap, future::Future, marker::PhantomData, sync::Arc};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Result,
+    messages::{ToolCall, ToolFunction, ToolMessage},
+};
+
+/// Declaration of a tool the model may call.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolDefinition {
+    /// Function name, e.g. `get_weather`
+    pub name: String,
+    /// What the function does and when the model should call it
+    pub description: String,
+    /// JSON schema (an `object` schema) describing the function's arguments
+    pub parameters: serde_json::Value,
+}
+
+impl ToolDefinition {
+    /// Create a new tool definition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orchestra_rs::tools::ToolDefinition;
+    ///
+    /// let tool = ToolDefinition::new(
+    ///     "get_weather",
+    ///     "Get the current weather for a city",
+    ///     serde_json::json!({
+    ///         "type": "object",
+    ///         "properties": {"city": {"type": "string"}},
+    ///         "required": ["city"]
+    ///     }),
+    /// );
+    /// assert_eq!(tool.name, "get_weather");
+    /// ```
+    pub fn new<N: Into<String>, D: Into<String>>(
+        name: N,
+        description: D,
+        parameters: serde_json::Value,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters,
+        }
+    }
+
+    /// Create a tool definition whose parameters are described by `T`
+    pub fn from_args<T: ToolArgs>(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self::new(name, description, T::parameters_schema())
+    }
+
+    /// Create a tool definition for a function that takes no arguments
+    pub fn without_parameters<N: Into<String>, D: Into<String>>(name: N, description: D) -> Self {
+        Self::new(name, description, serde_json::Value::Null)
+    }
+
+    /// Whether this tool declares any parameters
+    pub fn has_parameters(&self) -> bool {
+        !self.parameters.is_null()
+    }
+}
+
+/// A tool the model can call, together with its implementation.
+#[async_trait]
+pub trait Tool: Send + Sync {
+    /// Function name the model uses to call this tool
+    fn name(&self) -> &str;
+
+    /// What the tool does and when the model should call it
+    fn description(&self) -> &str;
+
+    /// JSON schema describing the tool's arguments.
+    ///
+    /// Defaults to `null`, meaning the tool takes no arguments.
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Execute the tool with the arguments chosen by the model
+    async fn call(&self, arguments: serde_json::Value) -> Result<serde_json::Value>;
+
+    /// The declaration sent to the model for this tool
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition::new(self.name(), self.description(), self.parameters())
+    }
+}
+
+/// A [`Tool`] made from an async function taking typed arguments.
+///
+/// The parameter schema comes from `A`, and the arguments chosen by the model are
+/// deserialized into `A` before `handler` runs; arguments that do not match are
+/// reported as [`OrchestraError::InvalidToolArguments`](crate::error::OrchestraError::InvalidToolArguments).
+pub struct FnTool<A, F> {
+    name: String,
+    description: String,
+    handler: F,
+    args: PhantomData<fn() -> A>,
+}
+
+impl<A, F, Fut> FnTool<A, F>
+where
+    A: ToolArgs,
+    F: Fn(A) -> Fut,
+    Fut: Future<Output = Result<serde_json::Value>>,
+{
+    /// Create a tool calling `handler` with the deserialized arguments
+    pub fn new<N: Into<String>, D: Into<String>>(name: N, description: D, handler: F) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            handler,
+            args: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<A, F, Fut> Tool for FnTool<A, F>
+where
+    A: ToolArgs + Send,
+    F: Fn(A) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<serde_json::Value>> + Send,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        A::parameters_schema()
+    }
+
+    async fn call(&self, arguments: serde_json::Value) -> Result<serde_json::Value> {
+        let function = ToolFunction {
+            name: self.name.clone(),
+            arguments,
+        };
+        let args = function.parse_arguments::<A>()?;
+        (self.handler)(args).await
+    }
+}
+
+/// A collection of [`Tool`]s, looked up by name when the model calls them.
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: BTreeMap<String, Arc<dyn Tool>>,
+}
+
+impl std::fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolRegistry")
+            .field("tools", &self.tools.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ToolRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tool, returning the registry for chaining
+    pub fn with_tool<T: Tool + 'static>(mut self, tool: T) -> Self {
+        self.register(tool);
+        self
+    }
+
+    /// Add an async function taking typed arguments as a tool; see [`FnTool`]
+    pub fn with_fn<A, F, Fut>(self, name: &str, description: &str, handler: F) -> Self
+    where
+        A: ToolArgs + Send + 'static,
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<serde_json::Value>> + Send + 'static,
+    {
+        self.with_tool(FnTool::new(name, description, handler))
+    }
+
+    /// Add a tool, replacing any tool previously registered under the same name
+    pub fn register<T: Tool + 'static>(&mut self, tool: T) {
+        self.tools.insert(tool.name().to_string(), Arc::new(tool));
+    }
+
+    /// Get a tool by name
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Tool>> {
+        self.tools.get(name)
+    }
+
+    /// Check if a tool with the given name is registered
+    pub fn contains(&self, name: &str) -> bool {
+        self.tools.contains_key(name)
+    }
+
+    /// Number of registered tools
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    /// Check if the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Declarations of all registered tools, ordered by name
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools.values().map(|tool| tool.definition()).collect()
+    }
+
+    /// Execute a tool call against the registry.
+    ///
+    /// Failures are not returned as errors: unknown tools and errors raised by the
+    /// tool are reported back as `{"error": "..."}` so the model can recover, for
+    /// example by retrying with different arguments.
+    pub async fn execute(&self, call: &ToolCall) -> ToolMessage {
+        let name = &call.function.name;
+
+        let response = match self.get(name) {
+            Some(tool) => match tool.call(call.function.arguments.clone()).await {
+                Ok(value) => value,
+                Err(e) => serde_json::json!({ "error": e.to_string() }),
+            },
+            None => serde_json::json!({ "error": format!("Unknown tool: {}", name) }),
+        };
+
+        ToolMessage::new(call.id.clone(), name.clone(), response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::OrchestraError,
+        messages::{ToolFunction, ToolResultContent},
+    };
+
+    struct Echo;
+
+    #[async_trait]
+    impl Tool for Echo {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo the arguments back"
+        }
+
+        fn parameters(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object", "properties": {"text": {"type": "string"}}})
+        }
+
+        async fn call(&self, arguments: serde_json::Value) -> Result<serde_json::Value> {
+            Ok(arguments)
+        }
+    }
+
+    struct Failing;
+
+    #[async_trait]
+    impl Tool for Failing {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn description(&self) -> &str {
+            "Always fails"
+        }
+
+        async fn call(&self, _arguments: serde_json::Value) -> Result<serde_json::Value> {
+            Err(OrchestraError::generic("disk full"))
+        }
+    }
+
+    #[derive(serde::Deserialize)]
+    struct AddArgs {
+        a: f64,
+        b: f64,
+    }
+
+    impl ToolSchema for AddArgs {
+        fn json_schema() -> serde_json::Value {
+            serde_json::json!({
+                "type": "object",
+                "properties": {"a": {"type": "number"}, "b": {"type": "number"}},
+                "required": ["a", "b"]
+            })
+        }
+    }
+
+    impl ToolArgs for AddArgs {}
+
+    fn call(name: &str, arguments: serde_json::Value) -> ToolCall {
+        ToolCall {
+            id: format!("{}_0", name),
+            call_id: None,
+            function: ToolFunction {
+                name: name.to_string(),
+                arguments,
+            },
+        }
+    }
+
+    #[test]
+    fn test_registry_definitions() {
+        let registry = ToolRegistry::new().with_tool(Failing).with_tool(Echo);
+
+        assert_eq!(registry.len(), 2);
+        assert!(registry.contains("echo"));
+        assert!(!registry.contains("missing"));
+
+        let definitions = registry.definitions();
+        assert_eq!(definitions[0].name, "echo");
+        assert_eq!(definitions[0].description, "Echo the arguments back");
+        assert!(definitions[0].has_parameters());
+        assert_eq!(definitions[1].name, "failing");
+        assert!(!definitions[1].has_parameters());
+    }
+
+    #[tokio::test]
+    async fn test_registry_execute() {
+        let registry = ToolRegistry::new().with_tool(Echo).with_tool(Failing);
+
+        let result = registry
+            .execute(&call("echo", serde_json::json!({"text": "hi"})))
+            .await;
+        assert_eq!(result.id, "echo_0");
+        assert_eq!(result.name, "echo");
+        assert_eq!(
+            result.content,
+            ToolResultContent::Json(serde_json::json!({"text": "hi"}))
+        );
+
+        let result = registry.execute(&call("failing", serde_json::json!({}))).await;
+        assert_eq!(
+            result.content.to_json(),
+            serde_json::json!({"error": "Orchestra error: disk full"})
+        );
+
+        let result = registry.execute(&call("missing", serde_json::json!({}))).await;
+        assert_eq!(
+            result.content.to_json(),
+            serde_json::json!({"error": "Unknown tool: missing"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fn_tool() {
+        let registry = ToolRegistry::new().with_fn("add", "Add two numbers", |args: AddArgs| async move {
+            Ok(serde_json::json!({ "sum": args.a + args.b }))
+        });
+
+        let definition = &registry.definitions()[0];
+        assert_eq!(definition.name, "add");
+        assert_eq!(definition.parameters, AddArgs::json_schema());
+
+        let result = registry
+            .execute(&call("add", serde_json::json!({"a": 1, "b": 2})))
+            .await;
+        assert_eq!(result.content.to_json(), serde_json::json!({"sum": 3.0}));
+
+        let tool = FnTool::new("add", "Add two numbers", |args: AddArgs| async move {
+            Ok(serde_json::json!(args.a + args.b))
+        });
+        assert!(matches!(
+            tool.call(serde_json::json!({"a": "one"})).await,
+            Err(OrchestraError::InvalidToolArguments { message, .. }) if message.starts_with("add: ")
+        ));
+    }
+}